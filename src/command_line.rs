@@ -52,8 +52,17 @@ pub(crate) enum Command {
     /// the side to write, either left (specified with values "left" or "l") or right (specified
     /// with values "right" or "r").
     Prompt,
-    /// Writes the ZSH script that initiates the prompt to the terminal output stream.
+    /// Writes the script that initiates the prompt to the terminal output stream. It accepts an
+    /// argument that specifies the shell to initiate, either "zsh" (the default), "bash",
+    /// "fish" or "nu".
     Init,
+    /// Writes the changes of the Git repository of the current directory to the terminal output
+    /// stream, either "clean" or "dirty" followed by their counts. It is meant to be run in the
+    /// background by the shell, as it can be slow in large repositories.
+    GitStatus,
+    /// Serves prompt requests through a per-user Unix socket until terminated, keeping the hardware
+    /// metadata cached between them. The prompt command requests to it when it is running.
+    Daemon,
 }
 
 impl Command {
@@ -65,6 +74,43 @@ impl Command {
         match self {
             Self::Init => "init",
             Self::Prompt => "prompt",
+            Self::GitStatus => "git-status",
+            Self::Daemon => "daemon",
+        }
+    }
+}
+
+/// Contains the options of the prompt command that require a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PromptOption {
+    /// Sets the shell whose syntax is used to write the prompt.
+    Shell,
+    /// Sets the exit code of the last command, for shells that can not expand it by themselves.
+    ExitCode,
+    /// Sets the total of background jobs, for shells that can not expand it by themselves.
+    JobsCount,
+    /// Sets the changes of the Git repository, for shells that compute them in the background.
+    GitStatus,
+    /// Sets the duration of the last command in milliseconds, measured by the shell hooks.
+    CommandDuration,
+}
+
+impl PromptOption {
+    /// Parses the option referred by a command-line argument.
+    ///
+    /// # Parameters
+    /// - `argument`: the argument to be parsed.
+    ///
+    /// # Returns
+    /// The option or `None` if the argument does not refer to any.
+    pub(crate) fn from_argument(argument: &str) -> Option<Self> {
+        match argument {
+            "-s" | "--shell" => Some(Self::Shell),
+            "-e" | "--exit-code" => Some(Self::ExitCode),
+            "-j" | "--jobs" => Some(Self::JobsCount),
+            "-g" | "--git-status" => Some(Self::GitStatus),
+            "-d" | "--duration" => Some(Self::CommandDuration),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,957 @@
+//! Provides features to load the user configuration file.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Result, anyhow, bail};
+use serde::Deserialize;
+
+use crate::prompt::Color;
+use crate::prompt::symbols::SymbolSet;
+
+/// The path of the configuration file relative to the user configuration directory.
+const CONFIG_FILE_PATH: &str = "river_dreams/config.toml";
+/// The environment variable that overrides the symbol set of the configuration file.
+const SYMBOL_SET_VARIABLE: &str = "RIVER_DREAMS_SYMBOL_SET";
+/// The time in milliseconds sections have to collect their data if it has not been overridden.
+const DEFAULT_SECTION_TIMEOUT: u64 = 1000;
+/// The minimum duration in milliseconds of the last command to be shown if it has not been
+/// overridden.
+const DEFAULT_SECTION_THRESHOLD: u64 = 2000;
+/// The ages in hours after which the data of a section is considered aging and stale if they have
+/// not been overridden.
+const DEFAULT_SECTION_AGE_THRESHOLDS: [u64; 2] = [24, 168];
+/// The maximum number of changed entries for which the lines changed are counted if it has not been
+/// overridden.
+const DEFAULT_SECTION_DIFF_LIMIT: usize = 500;
+/// The names of the entries that mark the root directory of a project if they have not been
+/// overridden.
+const DEFAULT_SECTION_PROJECT_MARKERS: [&str; 5] = [
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "pyproject.toml",
+    ".project-root",
+];
+/// The configuration used by sections that have not been configured by the user.
+static DEFAULT_SECTION_CONFIG: SectionConfig = SectionConfig {
+    enabled: true,
+    symbol: None,
+    color: None,
+    timeout: None,
+    threshold: None,
+    age_thresholds: None,
+    diff_limit: None,
+    expected_emails: None,
+    truncation: None,
+    project_markers: None,
+    nested_projects: None,
+    dirty_submodules: None,
+};
+
+/// Contains the prompt lines in which sections can be placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Line {
+    /// The left prompt line placed between the tribal separators.
+    LeftUpper,
+    /// The left prompt line in which commands are typed.
+    LeftLower,
+    /// The right prompt line.
+    Right,
+}
+
+impl Line {
+    /// Gets the name of the line as used in the configuration file.
+    ///
+    /// # Returns
+    /// The name.
+    pub(crate) const fn name(&self) -> &'static str {
+        match self {
+            Self::LeftUpper => "left_upper",
+            Self::LeftLower => "left_lower",
+            Self::Right => "right",
+        }
+    }
+}
+
+/// Contains the prompt sections that can be configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SectionId {
+    /// The section that shows the local IP address.
+    LocalIp,
+    /// The section that shows the disk usage.
+    Disk,
+    /// The section that shows the battery charge.
+    Battery,
+    /// The section that shows the calendar.
+    Calendar,
+    /// The section that shows the clock.
+    Clock,
+    /// The section that shows a decorator when the user is `root`.
+    UserPermissions,
+    /// The section that shows how long the last command took to run.
+    CommandDuration,
+    /// The section that shows the exit code of the last command.
+    ExitCode,
+    /// The section that shows the active Python virtual environment.
+    VirtualEnv,
+    /// The section that shows the current directory path.
+    Path,
+    /// The section that shows the Git repository state.
+    Git,
+    /// The section that shows the identity used to create Git commits.
+    GitIdentity,
+    /// The section that shows a decorator when the user does not own the current directory.
+    DirectoryOwnership,
+    /// The section that shows the total of each entry type in the current directory.
+    EntryTypeCounts,
+    /// The section that shows the total of jobs running in the background.
+    Jobs,
+}
+
+impl SectionId {
+    /// Gets the name of the section as used in the configuration file.
+    ///
+    /// # Returns
+    /// The name.
+    pub(crate) const fn name(&self) -> &'static str {
+        match self {
+            Self::LocalIp => "local_ip",
+            Self::Disk => "disk",
+            Self::Battery => "battery",
+            Self::Calendar => "calendar",
+            Self::Clock => "clock",
+            Self::UserPermissions => "user_permissions",
+            Self::CommandDuration => "command_duration",
+            Self::ExitCode => "exit_code",
+            Self::VirtualEnv => "virtual_env",
+            Self::Path => "path",
+            Self::Git => "git",
+            Self::GitIdentity => "git_identity",
+            Self::DirectoryOwnership => "directory_ownership",
+            Self::EntryTypeCounts => "entry_type_counts",
+            Self::Jobs => "jobs",
+        }
+    }
+
+    /// Gets the prompt line the section can be placed in.
+    ///
+    /// # Returns
+    /// The line.
+    pub(crate) const fn line(&self) -> Line {
+        match self {
+            Self::LocalIp | Self::Disk | Self::Battery | Self::Calendar | Self::Clock => {
+                Line::LeftUpper
+            }
+            Self::UserPermissions
+            | Self::CommandDuration
+            | Self::ExitCode
+            | Self::VirtualEnv
+            | Self::Path
+            | Self::Git
+            | Self::GitIdentity
+            | Self::DirectoryOwnership => Line::LeftLower,
+            Self::EntryTypeCounts | Self::Jobs => Line::Right,
+        }
+    }
+
+    /// Checks whether the section has a minimum threshold that can be overridden.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    const fn has_threshold(&self) -> bool {
+        matches!(self, Self::CommandDuration)
+    }
+
+    /// Checks whether the section has age thresholds that can be overridden.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    const fn has_age_thresholds(&self) -> bool {
+        matches!(self, Self::Git)
+    }
+
+    /// Checks whether the section has a diff limit that can be overridden.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    const fn has_diff_limit(&self) -> bool {
+        matches!(self, Self::Git)
+    }
+
+    /// Checks whether the section has expected emails that can be overridden.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    const fn has_expected_emails(&self) -> bool {
+        matches!(self, Self::GitIdentity)
+    }
+
+    /// Checks whether the section has a truncation that can be overridden.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    const fn has_truncation(&self) -> bool {
+        matches!(self, Self::Path)
+    }
+
+    /// Checks whether the section has project markers that can be overridden.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    const fn has_project_markers(&self) -> bool {
+        matches!(self, Self::Path)
+    }
+
+    /// Checks whether the section shows dirty submodules, which can be hidden.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    const fn has_dirty_submodules(&self) -> bool {
+        matches!(self, Self::Git)
+    }
+}
+
+/// Represents the order in which sections are placed in each prompt line.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct Layout {
+    /// The sections of the left prompt line placed between the tribal separators.
+    pub(crate) left_upper: Vec<SectionId>,
+    /// The sections of the left prompt line in which commands are typed.
+    pub(crate) left_lower: Vec<SectionId>,
+    /// The sections of the right prompt line.
+    pub(crate) right: Vec<SectionId>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            left_upper: vec![
+                SectionId::LocalIp,
+                SectionId::Disk,
+                SectionId::Battery,
+                SectionId::Calendar,
+                SectionId::Clock,
+            ],
+            left_lower: vec![
+                SectionId::UserPermissions,
+                SectionId::CommandDuration,
+                SectionId::ExitCode,
+                SectionId::VirtualEnv,
+                SectionId::Path,
+                SectionId::Git,
+                SectionId::GitIdentity,
+                SectionId::DirectoryOwnership,
+            ],
+            right: vec![SectionId::EntryTypeCounts, SectionId::Jobs],
+        }
+    }
+}
+
+impl Layout {
+    /// Gets the sections placed in a prompt line.
+    ///
+    /// # Parameters
+    /// - `line`: the line to be considered.
+    ///
+    /// # Returns
+    /// The sections.
+    fn sections(&self, line: Line) -> &[SectionId] {
+        match line {
+            Line::LeftUpper => &self.left_upper,
+            Line::LeftLower => &self.left_lower,
+            Line::Right => &self.right,
+        }
+    }
+}
+
+/// Contains the possible ways a path can be truncated to keep it short. The name of the repository
+/// root is always preserved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case", deny_unknown_fields)]
+pub(crate) enum Truncation {
+    /// Abbreviates the parent directories to their first character, like the Fish shell does. It
+    /// has no fields, but it is a struct so that unknown ones are rejected.
+    Fish {},
+    /// Keeps only the last components, replacing the others with an ellipsis.
+    LastComponents {
+        /// The number of components kept.
+        count: usize,
+    },
+    /// Limits the width in columns, replacing the middle of the path with an ellipsis.
+    MaxWidth {
+        /// The maximum width.
+        width: usize,
+    },
+}
+
+/// Represents the email expected to be used to create Git commits inside of a directory tree.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ExpectedEmail {
+    /// The directory at the top of the tree. It must be absolute or start with `~` to refer to the
+    /// home directory.
+    pub(crate) directory: String,
+    /// The pattern the email must match, ignoring case, in which `*` matches any sequence of
+    /// characters, e.g. `*@company.com`.
+    pub(crate) pattern: String,
+}
+
+impl ExpectedEmail {
+    /// Checks whether the directory at the top of the tree is absolute or relative to the home
+    /// directory, as the tree could never contain the current directory otherwise.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    fn has_absolute_directory(&self) -> bool {
+        self.directory == "~"
+            || self.directory.starts_with("~/")
+            || Path::new(&self.directory).is_absolute()
+    }
+
+    /// Gets the full path to the directory at the top of the tree.
+    ///
+    /// # Returns
+    /// The possible path or `None` if it refers to the home directory but it is not set.
+    fn directory_path(&self) -> Option<PathBuf> {
+        match self.directory.strip_prefix('~') {
+            Some(relative_path) if relative_path.is_empty() || relative_path.starts_with('/') => {
+                env::var_os("HOME")
+                    .filter(|home| !home.is_empty())
+                    .map(|home| PathBuf::from(home).join(relative_path.trim_start_matches('/')))
+            }
+            _ => Some(PathBuf::from(&self.directory)),
+        }
+    }
+
+    /// Checks whether an email matches the pattern.
+    ///
+    /// # Parameters
+    /// - `email`: the email to be checked.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    fn matches(&self, email: &str) -> bool {
+        let pattern = self.pattern.to_lowercase();
+        let email = email.to_lowercase();
+        let mut parts = pattern.split('*');
+        let mut remaining = match email.strip_prefix(parts.next().unwrap_or_default()) {
+            Some(remaining) => remaining,
+            None => return false,
+        };
+        let parts = parts.collect::<Vec<_>>();
+        let (last_part, middle_parts) = match parts.split_last() {
+            Some(parts) => parts,
+            None => return remaining.is_empty(),
+        };
+        for part in middle_parts {
+            remaining = match remaining.find(part) {
+                Some(index) => &remaining[index + part.len()..],
+                None => return false,
+            };
+        }
+        remaining.ends_with(last_part)
+    }
+}
+
+/// Represents the user customizations of a single section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct SectionConfig {
+    /// A boolean that states the section should be shown.
+    pub(crate) enabled: bool,
+    /// The symbol that replaces the default one. It is used verbatim, so it must include any
+    /// padding desired.
+    pub(crate) symbol: Option<String>,
+    /// The color that replaces the default one.
+    pub(crate) color: Option<Color>,
+    /// The time in milliseconds the section has to collect its data before a placeholder is shown
+    /// instead.
+    pub(crate) timeout: Option<u64>,
+    /// The minimum value in milliseconds the data of the section must reach to be shown.
+    pub(crate) threshold: Option<u64>,
+    /// The ages in hours after which the data of the section is considered aging and stale, in
+    /// ascending order.
+    pub(crate) age_thresholds: Option<[u64; 2]>,
+    /// The maximum number of changed entries for which the lines changed are counted, so that
+    /// large changes do not slow the section down. If zero, they are never counted.
+    pub(crate) diff_limit: Option<usize>,
+    /// The emails expected to be used to create Git commits inside of directory trees.
+    pub(crate) expected_emails: Option<Vec<ExpectedEmail>>,
+    /// The way the data of the section is truncated to keep it short. If `None`, it is not.
+    pub(crate) truncation: Option<Truncation>,
+    /// The names of the entries that mark the root directory of a project, such as `Cargo.toml`.
+    pub(crate) project_markers: Option<Vec<String>>,
+    /// Whether projects nested inside of repositories are preferred over the repository roots.
+    pub(crate) nested_projects: Option<bool>,
+    /// Whether the number of submodules with uncommited changes or a different commit checked out
+    /// is shown.
+    pub(crate) dirty_submodules: Option<bool>,
+}
+
+impl Default for SectionConfig {
+    fn default() -> Self {
+        DEFAULT_SECTION_CONFIG.clone()
+    }
+}
+
+impl SectionConfig {
+    /// Gets the symbol the section should use.
+    ///
+    /// # Parameters
+    /// - `default`: the symbol to be used if it has not been overridden.
+    ///
+    /// # Returns
+    /// The symbol.
+    pub(crate) fn symbol<'a>(&'a self, default: &'a str) -> &'a str {
+        self.symbol.as_deref().unwrap_or(default)
+    }
+
+    /// Gets the color the section should use.
+    ///
+    /// # Parameters
+    /// - `default`: the color to be used if it has not been overridden.
+    ///
+    /// # Returns
+    /// The color.
+    pub(crate) fn color(&self, default: Color) -> Color {
+        self.color.unwrap_or(default)
+    }
+
+    /// Gets the time the section has to collect its data.
+    ///
+    /// # Returns
+    /// The time.
+    pub(crate) fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout.unwrap_or(DEFAULT_SECTION_TIMEOUT))
+    }
+
+    /// Gets the minimum value the data of the section must reach to be shown.
+    ///
+    /// # Returns
+    /// The value.
+    pub(crate) fn threshold(&self) -> Duration {
+        Duration::from_millis(self.threshold.unwrap_or(DEFAULT_SECTION_THRESHOLD))
+    }
+
+    /// Gets the ages after which the data of the section is considered aging and stale.
+    ///
+    /// # Returns
+    /// The ages.
+    pub(crate) fn age_thresholds(&self) -> [Duration; 2] {
+        self.age_thresholds
+            .unwrap_or(DEFAULT_SECTION_AGE_THRESHOLDS)
+            .map(|hours| Duration::from_secs(hours * 3600))
+    }
+
+    /// Gets the maximum number of changed entries for which the lines changed are counted.
+    ///
+    /// # Returns
+    /// The number.
+    pub(crate) fn diff_limit(&self) -> usize {
+        self.diff_limit.unwrap_or(DEFAULT_SECTION_DIFF_LIMIT)
+    }
+
+    /// Gets the names of the entries that mark the root directory of a project.
+    ///
+    /// # Returns
+    /// The names.
+    pub(crate) fn project_markers(&self) -> Vec<&str> {
+        match &self.project_markers {
+            Some(project_markers) => project_markers.iter().map(String::as_str).collect(),
+            None => DEFAULT_SECTION_PROJECT_MARKERS.to_vec(),
+        }
+    }
+
+    /// Checks whether projects nested inside of repositories are preferred over the repository
+    /// roots, which is not the case by default.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    pub(crate) fn prefers_nested_projects(&self) -> bool {
+        self.nested_projects.unwrap_or(false)
+    }
+
+    /// Checks whether the number of dirty submodules is shown.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    pub(crate) fn shows_dirty_submodules(&self) -> bool {
+        self.dirty_submodules.unwrap_or(true)
+    }
+
+    /// Checks whether an email is expected to be used to create Git commits inside of a directory.
+    /// If more than one tree contains the directory, the innermost one is considered.
+    ///
+    /// # Parameters
+    /// - `directory`: the directory to be considered.
+    /// - `email`: the possible email to be checked.
+    ///
+    /// # Returns
+    /// A boolean that states that, which is always true if no tree contains the directory.
+    pub(crate) fn is_expected_email(&self, directory: &Path, email: Option<&str>) -> bool {
+        let expected_email = self
+            .expected_emails
+            .iter()
+            .flatten()
+            .filter_map(|expected_email| {
+                expected_email
+                    .directory_path()
+                    .filter(|path| directory.starts_with(path))
+                    .map(|path| (path.components().count(), expected_email))
+            })
+            .max_by_key(|(depth, _)| *depth);
+        match expected_email {
+            Some((_, expected_email)) => email.is_some_and(|email| expected_email.matches(email)),
+            None => true,
+        }
+    }
+}
+
+/// Represents the user configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub(crate) struct Config {
+    /// The set of symbols used by default in the sections and decorators.
+    pub(crate) symbol_set: SymbolSet,
+    /// The order in which sections are placed.
+    pub(crate) layout: Layout,
+    /// The customizations of each section.
+    sections: HashMap<SectionId, SectionConfig>,
+}
+
+impl Config {
+    /// Gets the customizations of a section.
+    ///
+    /// # Parameters
+    /// - `section`: the section to be considered.
+    ///
+    /// # Returns
+    /// The customizations.
+    pub(crate) fn section(&self, section: SectionId) -> &SectionConfig {
+        self.sections
+            .get(&section)
+            .unwrap_or(&DEFAULT_SECTION_CONFIG)
+    }
+
+    /// Gets the enabled sections placed in a prompt line, in their configured order.
+    ///
+    /// # Parameters
+    /// - `line`: the line to be considered.
+    ///
+    /// # Returns
+    /// An iterator over the sections.
+    pub(crate) fn enabled_sections(&self, line: Line) -> impl Iterator<Item = SectionId> + '_ {
+        self.layout
+            .sections(line)
+            .iter()
+            .copied()
+            .filter(|section| self.section(*section).enabled)
+    }
+
+    /// Checks whether the configuration is semantically valid.
+    ///
+    /// # Parameters
+    /// - `path`: the path of the configuration file, used in error messages.
+    ///
+    /// # Returns
+    /// A possible error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if a section is placed in the wrong line or more than once, or
+    /// if an override is not supported by a section.
+    fn validate(&self, path: &Path) -> Result<()> {
+        for line in [Line::LeftUpper, Line::LeftLower, Line::Right] {
+            let sections = self.layout.sections(line);
+            for (index, section) in sections.iter().enumerate() {
+                if section.line() != line {
+                    bail!(
+                        r#"section "{}" can not be placed in the "{}" layout line of "{}", only in "{}"."#,
+                        section.name(),
+                        line.name(),
+                        path.display(),
+                        section.line().name()
+                    );
+                }
+                if sections[..index].contains(section) {
+                    bail!(
+                        r#"section "{}" is placed more than once in the layout of "{}"."#,
+                        section.name(),
+                        path.display()
+                    );
+                }
+            }
+        }
+        for (section, section_config) in &self.sections {
+            if section_config.threshold.is_some() && !section.has_threshold() {
+                bail!(
+                    r#"section "{}" does not support a threshold in "{}"."#,
+                    section.name(),
+                    path.display()
+                );
+            }
+            match section_config.age_thresholds {
+                Some(_) if !section.has_age_thresholds() => bail!(
+                    r#"section "{}" does not support age thresholds in "{}"."#,
+                    section.name(),
+                    path.display()
+                ),
+                Some([aging, stale]) if aging > stale => bail!(
+                    r#"age thresholds of section "{}" are not in ascending order in "{}"."#,
+                    section.name(),
+                    path.display()
+                ),
+                _ => {}
+            }
+            if section_config.diff_limit.is_some() && !section.has_diff_limit() {
+                bail!(
+                    r#"section "{}" does not support a diff limit in "{}"."#,
+                    section.name(),
+                    path.display()
+                );
+            }
+            match &section_config.expected_emails {
+                Some(_) if !section.has_expected_emails() => bail!(
+                    r#"section "{}" does not support expected emails in "{}"."#,
+                    section.name(),
+                    path.display()
+                ),
+                Some(expected_emails) => {
+                    if let Some(expected_email) = expected_emails
+                        .iter()
+                        .find(|expected_email| !expected_email.has_absolute_directory())
+                    {
+                        bail!(
+                            r#"directory "{}" of section "{}" is not absolute nor starts with "~/" in "{}"."#,
+                            expected_email.directory,
+                            section.name(),
+                            path.display()
+                        );
+                    }
+                }
+                None => {}
+            }
+            match section_config.truncation {
+                Some(_) if !section.has_truncation() => bail!(
+                    r#"section "{}" does not support a truncation in "{}"."#,
+                    section.name(),
+                    path.display()
+                ),
+                Some(
+                    Truncation::LastComponents { count: 0 } | Truncation::MaxWidth { width: 0 },
+                ) => {
+                    bail!(
+                        r#"truncation of section "{}" must keep something of the data in "{}"."#,
+                        section.name(),
+                        path.display()
+                    )
+                }
+                _ => {}
+            }
+            match &section_config.project_markers {
+                Some(_) if !section.has_project_markers() => bail!(
+                    r#"section "{}" does not support project markers in "{}"."#,
+                    section.name(),
+                    path.display()
+                ),
+                Some(project_markers) => {
+                    if let Some(project_marker) = project_markers.iter().find(|project_marker| {
+                        project_marker.is_empty()
+                            || project_marker.contains('/')
+                            || [".", ".."].contains(&project_marker.as_str())
+                    }) {
+                        bail!(
+                            r#"project marker "{}" of section "{}" is not an entry name in "{}"."#,
+                            project_marker,
+                            section.name(),
+                            path.display()
+                        );
+                    }
+                }
+                None => {}
+            }
+            if section_config.nested_projects.is_some() && !section.has_project_markers() {
+                bail!(
+                    r#"section "{}" does not support nested projects in "{}"."#,
+                    section.name(),
+                    path.display()
+                );
+            }
+            if section_config.dirty_submodules.is_some() && !section.has_dirty_submodules() {
+                bail!(
+                    r#"section "{}" does not support hiding dirty submodules in "{}"."#,
+                    section.name(),
+                    path.display()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Gets the path of the configuration file. It is placed inside of `$XDG_CONFIG_HOME` or, if it is
+/// not set, inside of `~/.config`.
+///
+/// # Returns
+/// The possible path, if the user configuration directory can be resolved.
+fn file_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .filter(|directory| !directory.is_empty())
+                .map(|directory| PathBuf::from(directory).join(".config"))
+        })
+        .map(|directory| directory.join(CONFIG_FILE_PATH))
+}
+
+/// Loads the user configuration file. If it does not exist, the default configuration is used. The
+/// symbol set can be overridden through the `RIVER_DREAMS_SYMBOL_SET` environment variable.
+///
+/// # Returns
+/// The configuration or an error.
+///
+/// # Errors
+/// It returns a displayable error if the file exists but can not be read, is malformed or contains
+/// unknown keys and values, or if the environment variable contains an unknown symbol set.
+pub(crate) fn load() -> Result<Config> {
+    let mut config = read()?;
+    if let Some(name) = env::var(SYMBOL_SET_VARIABLE)
+        .ok()
+        .filter(|name| !name.is_empty())
+    {
+        config.symbol_set = SymbolSet::from_name(&name).ok_or_else(|| {
+            anyhow!(r#"invalid symbol set "{name}" provided through "{SYMBOL_SET_VARIABLE}"."#)
+        })?;
+    }
+    Ok(config)
+}
+
+/// Reads the user configuration file. If it does not exist, the default configuration is used.
+///
+/// # Returns
+/// The configuration or an error.
+///
+/// # Errors
+/// It returns a displayable error if the file exists but can not be read, is malformed or contains
+/// unknown keys and values.
+fn read() -> Result<Config> {
+    let path = match file_path() {
+        Some(path) => path,
+        None => return Ok(Config::default()),
+    };
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(_) => bail!(
+            r#"can not read the configuration file "{}"."#,
+            path.display()
+        ),
+    };
+    parse(&data, &path)
+}
+
+/// Parses the contents of a configuration file.
+///
+/// # Parameters
+/// - `data`: the contents of the file.
+/// - `path`: the path of the file, used in error messages.
+///
+/// # Returns
+/// The configuration or an error.
+///
+/// # Errors
+/// It returns a displayable error if the contents are malformed or contain unknown keys and values.
+fn parse(data: &str, path: &Path) -> Result<Config> {
+    let config = toml::from_str::<Config>(data).map_err(|error| {
+        let message = error.message().trim_end_matches('.');
+        match error.span() {
+            Some(span) => anyhow!(
+                r#"invalid configuration at line {} of "{}": {message}."#,
+                data[..span.start].matches('\n').count() + 1,
+                path.display()
+            ),
+            None => anyhow!(
+                r#"invalid configuration in "{}": {message}."#,
+                path.display()
+            ),
+        }
+    })?;
+    config.validate(path)?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a configuration, returning the message of its possible error.
+    fn parse_error(data: &str) -> Option<String> {
+        parse(data, Path::new("config.toml"))
+            .err()
+            .map(|error| error.to_string())
+    }
+
+    #[test]
+    fn empty_configurations_use_the_defaults() {
+        let config = parse("", Path::new("config.toml")).unwrap();
+        assert_eq!(config.symbol_set, SymbolSet::default());
+        assert_eq!(
+            config.layout.sections(Line::Right),
+            [SectionId::EntryTypeCounts, SectionId::Jobs]
+        );
+        assert!(config.section(SectionId::Battery).enabled);
+    }
+
+    #[test]
+    fn sections_are_customized() {
+        let config = parse(
+            r#"
+            [layout]
+            right = ["jobs"]
+
+            [sections.battery]
+            enabled = false
+
+            [sections.entry_type_counts]
+            symbol = "+"
+            color = "cyan"
+
+            [sections.git]
+            dirty_submodules = false
+            "#,
+            Path::new("config.toml"),
+        )
+        .unwrap();
+        assert_eq!(config.layout.sections(Line::Right), [SectionId::Jobs]);
+        assert!(!config.section(SectionId::Battery).enabled);
+        let entry_type_counts = config.section(SectionId::EntryTypeCounts);
+        assert_eq!(entry_type_counts.symbol("*"), "+");
+        assert_eq!(entry_type_counts.color, Some(Color::Cyan));
+        assert!(!config.section(SectionId::Git).shows_dirty_submodules());
+        assert!(config.section(SectionId::Path).shows_dirty_submodules());
+    }
+
+    #[test]
+    fn unknown_sections_are_rejected() {
+        let error = parse_error("[sections.weather]\nenabled = false").unwrap();
+        assert!(
+            error.starts_with("invalid configuration at line 1"),
+            "{error}"
+        );
+        let error = parse_error("[layout]\nright = [\"weather\"]").unwrap();
+        assert!(
+            error.starts_with("invalid configuration at line 2"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn invalid_colors_are_rejected() {
+        let error = parse_error("[sections.clock]\ncolor = \"purple\"").unwrap();
+        assert!(
+            error.starts_with("invalid configuration at line 2"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(parse_error("theme = \"dark\"").is_some());
+        assert!(parse_error("[sections.clock]\nsize = 2").is_some());
+        assert!(parse_error("[layout]\ncenter = []").is_some());
+    }
+
+    #[test]
+    fn unsupported_fields_are_rejected() {
+        assert_eq!(
+            parse_error("[sections.clock]\nthreshold = 10").as_deref(),
+            Some(r#"section "clock" does not support a threshold in "config.toml"."#)
+        );
+        assert_eq!(
+            parse_error("[sections.jobs]\ndiff_limit = 10").as_deref(),
+            Some(r#"section "jobs" does not support a diff limit in "config.toml"."#)
+        );
+        assert_eq!(
+            parse_error("[sections.path]\ndirty_submodules = false").as_deref(),
+            Some(r#"section "path" does not support hiding dirty submodules in "config.toml"."#)
+        );
+        assert_eq!(
+            parse_error("[sections.git]\nage_thresholds = [10, 5]").as_deref(),
+            Some(r#"age thresholds of section "git" are not in ascending order in "config.toml"."#)
+        );
+    }
+
+    #[test]
+    fn misplaced_sections_are_rejected() {
+        assert_eq!(
+            parse_error("[layout]\nright = [\"clock\"]").as_deref(),
+            Some(
+                r#"section "clock" can not be placed in the "right" layout line of "config.toml", only in "left_upper"."#
+            )
+        );
+        assert_eq!(
+            parse_error("[layout]\nright = [\"jobs\", \"jobs\"]").as_deref(),
+            Some(r#"section "jobs" is placed more than once in the layout of "config.toml"."#)
+        );
+    }
+
+    /// Creates an expected email for a pattern.
+    fn expected_email(pattern: &str) -> ExpectedEmail {
+        ExpectedEmail {
+            directory: String::from("~/work"),
+            pattern: String::from(pattern),
+        }
+    }
+
+    #[test]
+    fn patterns_without_wildcards_match_exactly() {
+        assert!(expected_email("user@company.com").matches("User@Company.com"));
+        assert!(!expected_email("user@company.com").matches("user@company.com.br"));
+        assert!(!expected_email("user@company.com").matches("other@company.com"));
+    }
+
+    #[test]
+    fn wildcards_match_any_sequence() {
+        assert!(expected_email("*@company.com").matches("user@company.com"));
+        assert!(expected_email("*@company.com").matches("@company.com"));
+        assert!(!expected_email("*@company.com").matches("user@personal.com"));
+        assert!(expected_email("user@*").matches("user@company.com"));
+        assert!(!expected_email("user@*").matches("other@company.com"));
+        assert!(expected_email("*@*.company.*").matches("user@team.company.com"));
+        assert!(!expected_email("*@*.company.*").matches("user@company.com"));
+        assert!(expected_email("*").matches("anything"));
+        assert!(!expected_email("a*a").matches("a"));
+    }
+
+    #[test]
+    fn empty_patterns_only_match_empty_emails() {
+        assert!(expected_email("").matches(""));
+        assert!(!expected_email("").matches("user@company.com"));
+    }
+
+    #[test]
+    fn relative_directories_are_rejected() {
+        let config = |directory: &str| {
+            format!(
+                "[[sections.git_identity.expected_emails]]\ndirectory = \"{directory}\"\npattern = \"*\""
+            )
+        };
+        assert_eq!(parse_error(&config("~/work")), None);
+        assert_eq!(parse_error(&config("/work")), None);
+        assert_eq!(
+            parse_error(&config("work")).as_deref(),
+            Some(
+                r#"directory "work" of section "git_identity" is not absolute nor starts with "~/" in "config.toml"."#
+            )
+        );
+        assert!(parse_error(&config("~user/work")).is_some());
+    }
+}
@@ -0,0 +1,822 @@
+//! Provides features to serve the prompt from a long-lived process through a per-user Unix socket.
+//!
+//! The daemon keeps the hardware metadata cached and forks itself to serve each request, so that
+//! its child inherits the cache and renders the prompt without the startup cost of a new process.
+//! The daemon has a single thread, as forking a process with many of them can leave the child
+//! deadlocked on locks held by the other threads, so the cache is refreshed by a child as well, so
+//! that slow hardware never delays the clients, and the children are reaped without waiting for
+//! them, so that they are served concurrently.
+//! The client sends its terminal output and error streams, allowing the child to write to them
+//! directly, along with its current directory, arguments and environment variables. Git metadata is
+//! never cached, as it changes between prompts, and neither is the discovery of the repository: it
+//! would have to run in the daemon before forking, delaying the other clients, and the libgit2
+//! repositories opened there would be shared with the children along with their stale caches of
+//! the configuration and references, while opening one only costs a few file reads.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, Read as _, Write as _};
+use std::mem;
+use std::net::IpAddr;
+use std::os::fd::{AsRawFd as _, FromRawFd as _, OwnedFd, RawFd};
+use std::os::unix::ffi::{OsStrExt as _, OsStringExt as _};
+use std::os::unix::fs::{DirBuilderExt as _, MetadataExt as _, PermissionsExt as _};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::process::{self, ExitCode};
+use std::ptr;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::hardware::{battery, disk};
+use crate::metadata::APP_METADATA;
+use crate::prompt;
+
+/// The name of the socket file.
+const SOCKET_FILE_NAME: &str = "river_dreams.sock";
+/// The interval in which the cache is refreshed.
+const CACHE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+/// The time the cache has to be refreshed before it is discarded, leaving the children to retrieve
+/// the hardware metadata by themselves.
+const CACHE_REFRESH_TIMEOUT: Duration = Duration::from_secs(2);
+/// The time the daemon waits for a client to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+/// The time the client waits for the daemon to reply with the status of its request, longer than
+/// sections usually take to collect their data.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+/// The status sent when the request has been rendered successfully.
+const SUCCESS_STATUS: u8 = 0;
+/// The status sent when the request has been rendered, but failed.
+const FAILURE_STATUS: u8 = 1;
+/// The status sent when the request could not be served, so the client must render it by itself.
+const NOT_SERVED_STATUS: u8 = 2;
+/// The request served by the current process, set only in the children of the daemon.
+static SERVED_REQUEST: OnceLock<ServedRequest> = OnceLock::new();
+
+/// Represents the hardware metadata kept by the daemon between requests.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cache {
+    /// The possible local IP address. If `None`, it could not be retrieved.
+    pub(crate) local_ip: Option<IpAddr>,
+    /// The possible disk usage. If `None`, it could not be retrieved.
+    pub(crate) disk_usage: Option<disk::Usage>,
+    /// The possible battery charge, which is `None` when there is no battery. If `None`, it could
+    /// not be retrieved.
+    pub(crate) battery_charge: Option<Option<battery::Charge>>,
+}
+
+impl Cache {
+    /// Collects the hardware metadata. Metadata that can not be retrieved is left for the children
+    /// to retrieve by themselves.
+    ///
+    /// # Returns
+    /// The cache.
+    fn collect() -> Self {
+        Self {
+            local_ip: local_ip_address::local_ip().ok(),
+            disk_usage: disk::usage().ok(),
+            battery_charge: battery::charge().ok(),
+        }
+    }
+
+    /// Allocates a buffer on the heap containing the cache encoded as fields separated by null
+    /// bytes. Empty fields represent metadata that could not be retrieved.
+    ///
+    /// # Returns
+    /// The buffer allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    fn encode(&self) -> Vec<u8> {
+        let local_ip = self
+            .local_ip
+            .map(|address| address.to_string())
+            .unwrap_or_default();
+        let disk_usage = self
+            .disk_usage
+            .map(|usage| usage.0.to_string())
+            .unwrap_or_default();
+        let battery_charge = match self.battery_charge {
+            None => String::new(),
+            Some(None) => String::from("-"),
+            Some(Some(charge)) if charge.is_charging => format!("{}+", charge.percentage),
+            Some(Some(charge)) => charge.percentage.to_string(),
+        };
+        [local_ip, disk_usage, battery_charge]
+            .join("\0")
+            .into_bytes()
+    }
+
+    /// Decodes a cache encoded by the `encode` method.
+    ///
+    /// # Parameters
+    /// - `data`: the data to be decoded.
+    ///
+    /// # Returns
+    /// The possible cache, if the data is valid.
+    fn decode(data: &[u8]) -> Option<Self> {
+        let mut fields = str::from_utf8(data).ok()?.split('\0');
+        let local_ip = match fields.next()? {
+            "" => None,
+            address => Some(address.parse().ok()?),
+        };
+        let disk_usage = match fields.next()? {
+            "" => None,
+            percentage => Some(disk::Usage(percentage.parse().ok()?)),
+        };
+        let battery_charge = match fields.next()? {
+            "" => None,
+            "-" => Some(None),
+            charge => {
+                let (percentage, is_charging) = match charge.strip_suffix('+') {
+                    Some(percentage) => (percentage, true),
+                    None => (charge, false),
+                };
+                Some(Some(battery::Charge {
+                    percentage: percentage.parse().ok()?,
+                    is_charging,
+                }))
+            }
+        };
+        fields.next().is_none().then_some(Self {
+            local_ip,
+            disk_usage,
+            battery_charge,
+        })
+    }
+}
+
+/// Represents a refresh of the cache running in a child of the daemon.
+struct CacheRefresh {
+    /// The ID of the child.
+    child: libc::pid_t,
+    /// The connection through which the child sends the cache collected.
+    stream: UnixStream,
+    /// The instant in which the refresh started.
+    started_at: Instant,
+}
+
+impl CacheRefresh {
+    /// Starts refreshing the cache in a child of the daemon.
+    ///
+    /// # Returns
+    /// The possible refresh, if the child can be created.
+    fn start() -> Option<Self> {
+        let (stream, mut child_stream) = UnixStream::pair().ok()?;
+        match unsafe { libc::fork() } {
+            -1 => None,
+            0 => {
+                drop(stream);
+                _ = child_stream.write_all(&Cache::collect().encode());
+                process::exit(0)
+            }
+            child => Some(Self {
+                child,
+                stream,
+                started_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Receives the cache collected by the child, which must have started sending it.
+    ///
+    /// # Returns
+    /// The cache or an empty one if it can not be received.
+    fn finish(mut self) -> Cache {
+        let mut data = Vec::new();
+        if self
+            .stream
+            .set_read_timeout(Some(CACHE_REFRESH_TIMEOUT))
+            .is_err()
+            || self.stream.read_to_end(&mut data).is_err()
+        {
+            self.cancel();
+            return Cache::default();
+        }
+        Cache::decode(&data).unwrap_or_default()
+    }
+
+    /// Terminates the child, discarding the cache it is collecting.
+    fn cancel(self) {
+        unsafe { libc::kill(self.child, libc::SIGKILL) };
+    }
+}
+
+/// Represents the data of the request served by a child of the daemon.
+struct ServedRequest {
+    /// The cache inherited from the daemon.
+    cache: Cache,
+    /// The possible terminal width of the client. If `None`, it could not be retrieved.
+    terminal_width: Option<prompt::Size>,
+}
+
+/// Represents a request sent by a client.
+struct Request {
+    /// The version of the application used by the client.
+    version: String,
+    /// The possible terminal width of the client. If `None`, it could not be retrieved.
+    terminal_width: Option<prompt::Size>,
+    /// The current directory of the client.
+    current_directory: PathBuf,
+    /// The command-line arguments of the client.
+    arguments: Vec<String>,
+    /// The environment variables of the client.
+    environment: Vec<(OsString, OsString)>,
+}
+
+impl Request {
+    /// Captures the request of the current process.
+    ///
+    /// # Parameters
+    /// - `arguments`: the command-line arguments to be sent.
+    ///
+    /// # Returns
+    /// The possible request, if the current directory can be resolved.
+    fn capture(arguments: &[String]) -> Option<Self> {
+        Some(Self {
+            version: String::from(APP_METADATA.version),
+            terminal_width: prompt::terminal_width().ok(),
+            current_directory: env::current_dir().ok()?,
+            arguments: arguments.to_vec(),
+            environment: env::vars_os().collect(),
+        })
+    }
+
+    /// Allocates a buffer on the heap containing the request encoded as fields separated by null
+    /// bytes.
+    ///
+    /// # Returns
+    /// The buffer allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    fn encode(&self) -> Vec<u8> {
+        let mut fields: Vec<&[u8]> = Vec::new();
+        let terminal_width = self
+            .terminal_width
+            .map(|width| width.to_string())
+            .unwrap_or_default();
+        let arguments_count = self.arguments.len().to_string();
+        fields.push(self.version.as_bytes());
+        fields.push(terminal_width.as_bytes());
+        fields.push(self.current_directory.as_os_str().as_bytes());
+        fields.push(arguments_count.as_bytes());
+        fields.extend(self.arguments.iter().map(|argument| argument.as_bytes()));
+        let variables = self
+            .environment
+            .iter()
+            .map(|(key, value)| [key.as_bytes(), b"=", value.as_bytes()].concat())
+            .collect::<Vec<_>>();
+        fields.extend(variables.iter().map(Vec::as_slice));
+        fields.join(&0)
+    }
+
+    /// Decodes a request encoded by the `encode` method.
+    ///
+    /// # Parameters
+    /// - `data`: the data to be decoded.
+    ///
+    /// # Returns
+    /// The possible request, if the data is valid.
+    fn decode(data: &[u8]) -> Option<Self> {
+        let mut fields = data.split(|byte| *byte == 0);
+        let version = String::from_utf8(fields.next()?.to_vec()).ok()?;
+        let terminal_width = match fields.next()? {
+            [] => None,
+            width => Some(str::from_utf8(width).ok()?.parse().ok()?),
+        };
+        let current_directory = PathBuf::from(OsStr::from_bytes(fields.next()?));
+        let arguments_count = str::from_utf8(fields.next()?).ok()?.parse().ok()?;
+        let mut arguments = Vec::with_capacity(arguments_count);
+        for _ in 0..arguments_count {
+            arguments.push(String::from_utf8(fields.next()?.to_vec()).ok()?);
+        }
+        let mut environment = Vec::new();
+        for variable in fields.filter(|variable| !variable.is_empty()) {
+            let separator = variable.iter().position(|byte| *byte == b'=')?;
+            environment.push((
+                OsString::from_vec(variable[..separator].to_vec()),
+                OsString::from_vec(variable[separator + 1..].to_vec()),
+            ));
+        }
+        Some(Self {
+            version,
+            terminal_width,
+            current_directory,
+            arguments,
+            environment,
+        })
+    }
+}
+
+/// Gets the directory in which the socket is placed: `$XDG_RUNTIME_DIR` or, if it is not set, a
+/// directory private to the user, with the user ID in its name, inside of the temporary directory,
+/// which is only created by the daemon.
+///
+/// # Returns
+/// The path and a boolean that states it is the private directory.
+fn socket_directory() -> (PathBuf, bool) {
+    match env::var_os("XDG_RUNTIME_DIR").filter(|directory| !directory.is_empty()) {
+        Some(directory) => (PathBuf::from(directory), false),
+        None => (
+            env::temp_dir().join(format!("river_dreams-{}", unsafe { libc::geteuid() })),
+            true,
+        ),
+    }
+}
+
+/// Gets the path of the socket. As other users can create entries in the temporary directory, the
+/// private directory is only used if it is not a symlink, is owned by the user and can not be
+/// accessed by anyone else.
+///
+/// # Returns
+/// The possible path or `None` if the private directory does not exist or is not safe.
+fn socket_path() -> Option<PathBuf> {
+    let (directory, is_private) = socket_directory();
+    if is_private {
+        let metadata = fs::symlink_metadata(&directory).ok()?;
+        if !metadata.is_dir()
+            || metadata.uid() != unsafe { libc::geteuid() }
+            || metadata.mode() & 0o077 != 0
+        {
+            return None;
+        }
+    }
+    Some(directory.join(SOCKET_FILE_NAME))
+}
+
+/// Gets the cache inherited from the daemon serving the current process.
+///
+/// # Returns
+/// The possible cache, if the current process is serving a request of the daemon.
+pub(crate) fn cache() -> Option<&'static Cache> {
+    SERVED_REQUEST.get().map(|request| &request.cache)
+}
+
+/// Gets the terminal width of the client whose request is served by the current process.
+///
+/// # Returns
+/// The possible width, if the current process is serving a request of the daemon and the client
+/// could retrieve it.
+pub(crate) fn terminal_width() -> Option<prompt::Size> {
+    SERVED_REQUEST
+        .get()
+        .and_then(|request| request.terminal_width)
+}
+
+/// Requests the daemon to run the application with some command-line arguments on behalf of the
+/// current process.
+///
+/// # Parameters
+/// - `arguments`: the command-line arguments to be run.
+///
+/// # Returns
+/// The exit code of the request or `None` if the daemon is not running, can not serve it or does
+/// not reply in time, in which case the current process must run it by itself. Nothing is sent if the process listening
+/// on the socket is not owned by the user, as the request contains the environment variables.
+pub(crate) fn request(arguments: &[String]) -> Option<ExitCode> {
+    let mut stream = UnixStream::connect(socket_path()?).ok()?;
+    if peer_user_id(&stream) != Some(unsafe { libc::geteuid() }) {
+        return None;
+    }
+    let data = Request::capture(arguments)?.encode();
+    send_descriptors(
+        &stream,
+        &(data.len() as u32).to_le_bytes(),
+        [io::stdout().as_raw_fd(), io::stderr().as_raw_fd()],
+    )
+    .ok()?;
+    stream.write_all(&data).ok()?;
+    let mut status = [NOT_SERVED_STATUS];
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT)).ok()?;
+    stream.read_exact(&mut status).ok()?;
+    match status[0] {
+        SUCCESS_STATUS => Some(ExitCode::SUCCESS),
+        NOT_SERVED_STATUS => None,
+        _ => Some(ExitCode::FAILURE),
+    }
+}
+
+/// Serves the requests of clients until the process is terminated.
+///
+/// # Parameters
+/// - `run`: the function that runs the application with the command-line arguments of a request.
+///
+/// # Returns
+/// An error if it can not start serving.
+///
+/// # Errors
+/// It returns a displayable error if another daemon is already running or if the socket or its
+/// private directory can not be created.
+pub(crate) fn serve(run: fn(&[String]) -> ExitCode) -> Result<()> {
+    if let (directory, true) = socket_directory() {
+        _ = fs::DirBuilder::new().mode(0o700).create(directory);
+    }
+    let path = socket_path()
+        .ok_or_else(|| anyhow!("can not create a private directory for the socket."))?;
+    if UnixStream::connect(&path).is_ok() {
+        bail!(r#"a daemon is already listening on "{}"."#, path.display());
+    }
+    _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .map_err(|_| anyhow!(r#"can not listen on "{}"."#, path.display()))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).map_err(|_| {
+        anyhow!(
+            r#"can not restrict the permissions of "{}"."#,
+            path.display()
+        )
+    })?;
+    let mut cache = Cache::default();
+    let mut refresh = CacheRefresh::start();
+    let mut refreshed_at = Instant::now();
+    loop {
+        let timeout = match &refresh {
+            Some(pending) => CACHE_REFRESH_TIMEOUT.saturating_sub(pending.started_at.elapsed()),
+            None => CACHE_REFRESH_INTERVAL.saturating_sub(refreshed_at.elapsed()),
+        };
+        let [has_client, has_cache] = wait(&listener, refresh.as_ref(), timeout);
+        if has_client && let Ok((stream, _)) = listener.accept() {
+            fork_client(stream, &cache, run);
+        }
+        match refresh.take() {
+            Some(pending) if has_cache => {
+                cache = pending.finish();
+                refreshed_at = Instant::now();
+            }
+            Some(pending) if pending.started_at.elapsed() >= CACHE_REFRESH_TIMEOUT => {
+                pending.cancel();
+                cache = Cache::default();
+                refreshed_at = Instant::now();
+            }
+            Some(pending) => refresh = Some(pending),
+            None if refreshed_at.elapsed() >= CACHE_REFRESH_INTERVAL => {
+                refresh = CacheRefresh::start();
+                refreshed_at = Instant::now();
+            }
+            None => {}
+        }
+        reap_children();
+    }
+}
+
+/// Waits for a client to connect to the socket or for the child refreshing the cache to send it.
+///
+/// # Parameters
+/// - `listener`: the socket to be watched.
+/// - `refresh`: the possible refresh of the cache to be watched.
+/// - `timeout`: the maximum time to wait.
+///
+/// # Returns
+/// Booleans that state a client is waiting to be accepted and the cache can be received.
+fn wait(listener: &UnixListener, refresh: Option<&CacheRefresh>, timeout: Duration) -> [bool; 2] {
+    let mut descriptors = [
+        listener.as_raw_fd(),
+        refresh.map_or(-1, |refresh| refresh.stream.as_raw_fd()),
+    ]
+    .map(|descriptor| libc::pollfd {
+        fd: descriptor,
+        events: libc::POLLIN,
+        revents: 0,
+    });
+    if unsafe {
+        libc::poll(
+            descriptors.as_mut_ptr(),
+            descriptors.len() as libc::nfds_t,
+            timeout.as_millis() as libc::c_int,
+        )
+    } <= 0
+    {
+        return [false; 2];
+    }
+    descriptors.map(|descriptor| descriptor.revents != 0)
+}
+
+/// Forks the daemon to serve the request of a client in the child, so that the daemon can accept
+/// other clients while it is served.
+///
+/// # Parameters
+/// - `stream`: the connection with the client.
+/// - `cache`: the cache to be inherited by the child.
+/// - `run`: the function that runs the application with the command-line arguments of a request.
+fn fork_client(mut stream: UnixStream, cache: &Cache, run: fn(&[String]) -> ExitCode) {
+    match unsafe { libc::fork() } {
+        -1 => _ = stream.write_all(&[NOT_SERVED_STATUS]),
+        0 => serve_client(stream, cache, run),
+        _ => {}
+    }
+}
+
+/// Reaps the children of the daemon that have already terminated, without waiting for the others.
+fn reap_children() {
+    while unsafe { libc::waitpid(-1, ptr::null_mut(), libc::WNOHANG) } > 0 {}
+}
+
+/// Serves the request of a client in the child of the daemon, replying with its status, then
+/// terminates it.
+///
+/// # Parameters
+/// - `stream`: the connection with the client.
+/// - `cache`: the cache inherited from the daemon.
+/// - `run`: the function that runs the application with the command-line arguments of a request.
+fn serve_client(mut stream: UnixStream, cache: &Cache, run: fn(&[String]) -> ExitCode) -> ! {
+    let status = if peer_user_id(&stream) == Some(unsafe { libc::geteuid() })
+        && stream.set_read_timeout(Some(REQUEST_TIMEOUT)).is_ok()
+        && let Some((request, descriptors)) = receive_request(&mut stream)
+        && request.version == APP_METADATA.version
+    {
+        render(request, descriptors, cache, run)
+    } else {
+        NOT_SERVED_STATUS
+    };
+    _ = stream.write_all(&[status]);
+    process::exit(status.into())
+}
+
+/// Receives the request of a client along with its terminal output and error streams.
+///
+/// # Parameters
+/// - `stream`: the connection with the client.
+///
+/// # Returns
+/// The possible request and streams, if they are valid.
+fn receive_request(stream: &mut UnixStream) -> Option<(Request, [OwnedFd; 2])> {
+    let mut length = [0; 4];
+    let descriptors = receive_descriptors(stream, &mut length).ok()?;
+    let mut data = vec![0; u32::from_le_bytes(length) as usize];
+    stream.read_exact(&mut data).ok()?;
+    Some((Request::decode(&data)?, descriptors))
+}
+
+/// Renders the request of a client in the child of the daemon.
+///
+/// # Parameters
+/// - `request`: the request to be rendered.
+/// - `descriptors`: the terminal output and error streams of the client.
+/// - `cache`: the cache inherited from the daemon.
+/// - `run`: the function that runs the application with the command-line arguments of a request.
+///
+/// # Returns
+/// The status to be replied.
+fn render(
+    request: Request,
+    descriptors: [OwnedFd; 2],
+    cache: &Cache,
+    run: fn(&[String]) -> ExitCode,
+) -> u8 {
+    for (descriptor, target) in descriptors
+        .iter()
+        .zip([libc::STDOUT_FILENO, libc::STDERR_FILENO])
+    {
+        if unsafe { libc::dup2(descriptor.as_raw_fd(), target) } < 0 {
+            return NOT_SERVED_STATUS;
+        }
+    }
+    if env::set_current_dir(&request.current_directory).is_err() {
+        return NOT_SERVED_STATUS;
+    }
+    // The environment can be safely modified as the daemon has a single thread, so no other one can
+    // be holding its lock.
+    for (key, _) in env::vars_os() {
+        unsafe { env::remove_var(key) };
+    }
+    for (key, value) in &request.environment {
+        unsafe { env::set_var(key, value) };
+    }
+    _ = SERVED_REQUEST.set(ServedRequest {
+        cache: cache.clone(),
+        terminal_width: request.terminal_width,
+    });
+    let exit_code = run(&request.arguments);
+    _ = io::stdout().flush();
+    if exit_code == ExitCode::SUCCESS {
+        SUCCESS_STATUS
+    } else {
+        FAILURE_STATUS
+    }
+}
+
+/// Gets the size of the control message used to transfer the terminal streams.
+///
+/// # Returns
+/// The size in bytes.
+fn control_message_space() -> usize {
+    unsafe { libc::CMSG_SPACE(mem::size_of::<[RawFd; 2]>() as libc::c_uint) as usize }
+}
+
+/// Sends data through a socket along with file descriptors.
+///
+/// # Parameters
+/// - `stream`: the connection to be used.
+/// - `data`: the data to be sent.
+/// - `descriptors`: the file descriptors to be sent.
+///
+/// # Returns
+/// A possible error.
+///
+/// # Errors
+/// It returns an error if the message can not be sent entirely.
+fn send_descriptors(stream: &UnixStream, data: &[u8], descriptors: [RawFd; 2]) -> io::Result<()> {
+    let mut io_vector = libc::iovec {
+        iov_base: data.as_ptr().cast_mut().cast(),
+        iov_len: data.len(),
+    };
+    let mut control = vec![0_u64; control_message_space().div_ceil(mem::size_of::<u64>())];
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_iov = &mut io_vector;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = control_message_space() as _;
+    unsafe {
+        let header = libc::CMSG_FIRSTHDR(&message);
+        (*header).cmsg_level = libc::SOL_SOCKET;
+        (*header).cmsg_type = libc::SCM_RIGHTS;
+        (*header).cmsg_len = libc::CMSG_LEN(mem::size_of_val(&descriptors) as libc::c_uint) as _;
+        ptr::copy_nonoverlapping(
+            descriptors.as_ptr().cast::<u8>(),
+            libc::CMSG_DATA(header),
+            mem::size_of_val(&descriptors),
+        );
+    }
+    match unsafe { libc::sendmsg(stream.as_raw_fd(), &message, 0) } {
+        length if length < 0 => Err(io::Error::last_os_error()),
+        length if length as usize != data.len() => Err(io::Error::from(io::ErrorKind::WriteZero)),
+        _ => Ok(()),
+    }
+}
+
+/// Receives data through a socket along with the file descriptors sent by the `send_descriptors`
+/// function.
+///
+/// # Parameters
+/// - `stream`: the connection to be used.
+/// - `data`: the buffer to be filled, whose length must match the data sent.
+///
+/// # Returns
+/// The file descriptors received or an error.
+///
+/// # Errors
+/// It returns an error if the message can not be received entirely or does not contain the file
+/// descriptors.
+fn receive_descriptors(stream: &UnixStream, data: &mut [u8]) -> io::Result<[OwnedFd; 2]> {
+    let mut io_vector = libc::iovec {
+        iov_base: data.as_mut_ptr().cast(),
+        iov_len: data.len(),
+    };
+    let mut control = vec![0_u64; control_message_space().div_ceil(mem::size_of::<u64>())];
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_iov = &mut io_vector;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = control_message_space() as _;
+    let length = unsafe { libc::recvmsg(stream.as_raw_fd(), &mut message, 0) };
+    if length < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut raw_descriptors: [RawFd; 2] = [-1; 2];
+    unsafe {
+        let header = libc::CMSG_FIRSTHDR(&message);
+        if header.is_null()
+            || (*header).cmsg_level != libc::SOL_SOCKET
+            || (*header).cmsg_type != libc::SCM_RIGHTS
+            || (*header).cmsg_len as usize
+                != libc::CMSG_LEN(mem::size_of_val(&raw_descriptors) as libc::c_uint) as usize
+        {
+            return Err(io::Error::from(io::ErrorKind::InvalidData));
+        }
+        ptr::copy_nonoverlapping(
+            libc::CMSG_DATA(header),
+            raw_descriptors.as_mut_ptr().cast::<u8>(),
+            mem::size_of_val(&raw_descriptors),
+        );
+    }
+    let descriptors = raw_descriptors.map(|descriptor| unsafe { OwnedFd::from_raw_fd(descriptor) });
+    if length as usize != data.len() || message.msg_flags & libc::MSG_CTRUNC != 0 {
+        return Err(io::Error::from(io::ErrorKind::InvalidData));
+    }
+    Ok(descriptors)
+}
+
+/// Gets the ID of the user that owns the process connected to a socket.
+///
+/// # Parameters
+/// - `stream`: the connection to be considered.
+///
+/// # Returns
+/// The possible ID, if it can be retrieved.
+#[cfg(target_os = "linux")]
+fn peer_user_id(stream: &UnixStream) -> Option<libc::uid_t> {
+    let mut credentials: libc::ucred = unsafe { mem::zeroed() };
+    let mut length = mem::size_of::<libc::ucred>() as libc::socklen_t;
+    (unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&raw mut credentials).cast(),
+            &mut length,
+        )
+    } == 0)
+        .then_some(credentials.uid)
+}
+
+/// Gets the ID of the user that owns the process connected to a socket.
+///
+/// # Parameters
+/// - `stream`: the connection to be considered.
+///
+/// # Returns
+/// The possible ID, if it can be retrieved.
+#[cfg(target_os = "macos")]
+fn peer_user_id(stream: &UnixStream) -> Option<libc::uid_t> {
+    let mut user_id = 0;
+    let mut group_id = 0;
+    (unsafe { libc::getpeereid(stream.as_raw_fd(), &mut user_id, &mut group_id) } == 0)
+        .then_some(user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_encoded_and_decoded() {
+        let request = Request {
+            version: String::from("1.0.0"),
+            terminal_width: Some(80),
+            current_directory: PathBuf::from("/home/user/with space"),
+            arguments: vec![String::from("prompt"), String::from("left")],
+            environment: vec![
+                (OsString::from("HOME"), OsString::from("/home/user")),
+                (OsString::from("EQUATION"), OsString::from("a=b")),
+                (OsString::from("EMPTY"), OsString::new()),
+            ],
+        };
+        let decoded = Request::decode(&request.encode()).unwrap();
+        assert_eq!(decoded.version, request.version);
+        assert_eq!(decoded.terminal_width, request.terminal_width);
+        assert_eq!(decoded.current_directory, request.current_directory);
+        assert_eq!(decoded.arguments, request.arguments);
+        assert_eq!(decoded.environment, request.environment);
+    }
+
+    #[test]
+    fn requests_without_optional_data_are_encoded_and_decoded() {
+        let request = Request {
+            version: String::from("1.0.0"),
+            terminal_width: None,
+            current_directory: PathBuf::from("/"),
+            arguments: Vec::new(),
+            environment: Vec::new(),
+        };
+        let decoded = Request::decode(&request.encode()).unwrap();
+        assert_eq!(decoded.terminal_width, None);
+        assert!(decoded.arguments.is_empty());
+        assert!(decoded.environment.is_empty());
+    }
+
+    #[test]
+    fn malformed_requests_are_rejected() {
+        assert!(Request::decode(b"").is_none());
+        assert!(Request::decode(b"1.0.0\x00wide\x00/\x000").is_none());
+        assert!(Request::decode(b"1.0.0\x0080\x00/\x002\x00prompt").is_none());
+        assert!(Request::decode(b"1.0.0\x0080\x00/\x000\x00VARIABLE").is_none());
+        assert!(Request::decode(b"1.0.0\x0080\x00/\x000\x00\xff=value").is_some());
+        assert!(Request::decode(b"1.0.0\x0080\x00/\x001\x00\xff").is_none());
+    }
+
+    #[test]
+    fn caches_are_encoded_and_decoded() {
+        let caches = [
+            Cache::default(),
+            Cache {
+                local_ip: "192.168.0.2".parse().ok(),
+                disk_usage: Some(disk::Usage(42)),
+                battery_charge: Some(None),
+            },
+            Cache {
+                local_ip: "fe80::1".parse().ok(),
+                disk_usage: None,
+                battery_charge: Some(Some(battery::Charge {
+                    percentage: 100,
+                    is_charging: true,
+                })),
+            },
+        ];
+        for cache in caches {
+            let decoded = Cache::decode(&cache.encode()).unwrap();
+            assert_eq!(format!("{decoded:?}"), format!("{cache:?}"));
+        }
+    }
+
+    #[test]
+    fn malformed_caches_are_rejected() {
+        assert!(Cache::decode(b"").is_none());
+        assert!(Cache::decode(b"address\x00\x00").is_none());
+        assert!(Cache::decode(b"\x00full\x00").is_none());
+        assert!(Cache::decode(b"\x00\x00+").is_none());
+        assert!(Cache::decode(b"\x00\x00\x00").is_none());
+    }
+}
@@ -1,5 +1,7 @@
 //! Provides features related to date time resolutions.
 
+use std::time::Duration;
+
 use chrono::{DateTime, Datelike as _, TimeZone, Timelike as _};
 
 /// Represents a date ordinal.
@@ -17,6 +19,16 @@ pub(crate) trait DateTimeResolutions {
     /// # Returns
     /// The fraction.
     fn day_fraction(&self) -> DayFraction;
+    /// Gets the time elapsed from the object until a later date time, e.g. the age of a commit.
+    ///
+    /// # Parameters
+    /// - `later`: the later date time.
+    ///
+    /// # Returns
+    /// The time elapsed or zero if the date time is not earlier.
+    fn elapsed_until<U>(&self, later: &DateTime<U>) -> Duration
+    where
+        U: TimeZone;
 }
 
 impl<T> DateTimeResolutions for DateTime<T>
@@ -38,6 +50,17 @@ where
     fn day_fraction(&self) -> DayFraction {
         DayFraction::from(self)
     }
+
+    fn elapsed_until<U>(&self, later: &DateTime<U>) -> Duration
+    where
+        U: TimeZone,
+    {
+        later
+            .naive_utc()
+            .signed_duration_since(self.naive_utc())
+            .to_std()
+            .unwrap_or_default()
+    }
 }
 
 /// Contains the possible fractions of the day.
@@ -72,10 +95,10 @@ where
 /// # Parameters
 /// - `datetime`: the date time to be checked.
 /// - `ordinal`: the ordinal to be compared to. It can be: 1 (refering to
-/// first, "st"), 2 (refering to second, "nd") or 3 (refering to third, "rd").
+///   first, "st"), 2 (refering to second, "nd") or 3 (refering to third, "rd").
 ///
 /// # Returns
 /// A boolean that states that.
 fn is_ordinal(datetime: &DateTime<impl TimeZone>, ordinal: Ordinal) -> bool {
-    (datetime.day() - ordinal as u32) % 10 == 0
+    (datetime.day() - ordinal as u32).is_multiple_of(10)
 }
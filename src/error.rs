@@ -15,7 +15,7 @@ use crate::metadata;
 /// # Remarks
 /// - If a write error gets thrown, it simply gets ignored.
 /// - Ideally, the application execution should be terminated from the `main` fn right after its
-/// call if the error cannot be handled, allowing proper resources deallocation.
+///   call if the error cannot be handled, allowing proper resources deallocation.
 pub(crate) fn write(error: Error) {
     let mut stderr = io::stderr().lock();
     _ = writeln!(
@@ -3,7 +3,7 @@
 pub(crate) mod entry;
 
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Result, anyhow};
 
@@ -26,6 +26,26 @@ pub(crate) fn current() -> Result<PathBuf> {
     })
 }
 
+/// Finds the nearest directory containing any of some entries by searching recursively from a
+/// directory towards the file system root.
+///
+/// # Parameters
+/// - `directory`: the directory to start searching from.
+/// - `names`: the names of the entries to search for.
+///
+/// # Returns
+/// The possible path of the directory found.
+pub(crate) fn find_nearest_containing(directory: &Path, names: &[&str]) -> Option<PathBuf> {
+    directory
+        .ancestors()
+        .find(|ancestor| {
+            names
+                .iter()
+                .any(|name| ancestor.join(name).symlink_metadata().is_ok())
+        })
+        .map(Path::to_path_buf)
+}
+
 /// Checks if the user owns the current directory, this is, has write permissions.
 ///
 /// # Returns
@@ -1,5 +1,6 @@
 //! Provides features to perform path resolutions.
 
+use std::env;
 use std::path::Path;
 
 /// Provides members to resolve paths attributes.
@@ -9,6 +10,16 @@ pub(crate) trait PathResolutions {
     /// # Returns
     /// A boolean that states that.
     fn is_root(&self) -> bool;
+
+    /// Allocates a string on the heap containing the path with the home directory abbreviated as
+    /// `~`, if the path is inside of it.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    fn abbreviate_home(&self) -> String;
 }
 
 impl<T> PathResolutions for T
@@ -18,4 +29,15 @@ where
     fn is_root(&self) -> bool {
         self.as_ref().ancestors().count() == 1
     }
+
+    fn abbreviate_home(&self) -> String {
+        match env::var_os("HOME")
+            .filter(|home| !home.is_empty())
+            .and_then(|home| self.as_ref().strip_prefix(home).ok())
+        {
+            Some(relative_path) if relative_path.as_os_str().is_empty() => String::from("~"),
+            Some(relative_path) => format!("~/{}", relative_path.display()),
+            None => self.as_ref().display().to_string(),
+        }
+    }
 }
@@ -2,6 +2,7 @@
 
 use std::any;
 use std::fmt::Display;
+use std::time::Duration;
 
 use anyhow::{Result, anyhow};
 use num_traits::{PrimInt, Unsigned};
@@ -37,3 +38,110 @@ where
     }
     Ok(length)
 }
+
+/// Allocates a string on the heap containing an age in a compact human-friendly format that uses
+/// only its largest unit, e.g: `45s`, `3h`, `2d`, `5w` or `1y`.
+///
+/// # Parameters
+/// - `age`: the age to be formatted.
+///
+/// # Returns
+/// The string allocated.
+///
+/// # Panics
+/// It panics with a "memory allocation failed" message if the allocation fails.
+pub(crate) fn age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        86400..604800 => format!("{}d", seconds / 86400),
+        604800..31536000 => format!("{}w", seconds / 604800),
+        _ => format!("{}y", seconds / 31536000),
+    }
+}
+
+/// Allocates a string on the heap containing a duration in a human-friendly format, e.g: `850ms`,
+/// `40s`, `1m03s` or `2h05m09s`. Durations of a second or longer are truncated to whole seconds.
+///
+/// # Parameters
+/// - `duration`: the duration to be formatted.
+///
+/// # Returns
+/// The string allocated.
+///
+/// # Panics
+/// It panics with a "memory allocation failed" message if the allocation fails.
+pub(crate) fn duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0 => format!("{}ms", duration.as_millis()),
+        1..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!(
+            "{}h{:02}m{:02}s",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_shorter_than_a_second_use_milliseconds() {
+        assert_eq!(duration(Duration::ZERO), "0ms");
+        assert_eq!(duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(duration(Duration::from_millis(999)), "999ms");
+    }
+
+    #[test]
+    fn durations_are_truncated_to_seconds() {
+        assert_eq!(duration(Duration::from_millis(1000)), "1s");
+        assert_eq!(duration(Duration::from_millis(1999)), "1s");
+        assert_eq!(duration(Duration::from_secs(59)), "59s");
+    }
+
+    #[test]
+    fn durations_of_minutes_and_hours_are_padded() {
+        assert_eq!(duration(Duration::from_secs(60)), "1m00s");
+        assert_eq!(duration(Duration::from_secs(63)), "1m03s");
+        assert_eq!(duration(Duration::from_secs(3599)), "59m59s");
+        assert_eq!(duration(Duration::from_secs(3600)), "1h00m00s");
+        assert_eq!(duration(Duration::from_secs(7509)), "2h05m09s");
+    }
+
+    #[test]
+    fn durations_of_days_are_shown_in_hours() {
+        assert_eq!(duration(Duration::from_secs(86400)), "24h00m00s");
+        assert_eq!(duration(Duration::from_secs(3 * 86400 + 61)), "72h01m01s");
+    }
+
+    #[test]
+    fn ages_use_seconds_and_minutes() {
+        assert_eq!(age(Duration::ZERO), "0s");
+        assert_eq!(age(Duration::from_millis(59999)), "59s");
+        assert_eq!(age(Duration::from_secs(60)), "1m");
+        assert_eq!(age(Duration::from_secs(3599)), "59m");
+    }
+
+    #[test]
+    fn ages_use_hours_and_days() {
+        assert_eq!(age(Duration::from_secs(3600)), "1h");
+        assert_eq!(age(Duration::from_secs(86399)), "23h");
+        assert_eq!(age(Duration::from_secs(86400)), "1d");
+        assert_eq!(age(Duration::from_secs(604799)), "6d");
+    }
+
+    #[test]
+    fn ages_use_weeks_and_years() {
+        assert_eq!(age(Duration::from_secs(604800)), "1w");
+        assert_eq!(age(Duration::from_secs(31535999)), "52w");
+        assert_eq!(age(Duration::from_secs(31536000)), "1y");
+        assert_eq!(age(Duration::from_secs(3 * 31536000 + 1)), "3y");
+    }
+}
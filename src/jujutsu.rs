@@ -0,0 +1,600 @@
+//! Provides features to retrieve Jujutsu repositories metadata by reading their files directly,
+//! without running the `jj` command. Only repositories backed by Git, the default, are supported.
+
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use git2::{Commit, Oid};
+
+use crate::vcs::{self, Kind, Summary};
+
+/// The name of the directory in which Jujutsu stores the metadata of a workspace.
+const METADATA_DIRECTORY: &str = ".jj";
+/// The name Jujutsu gives to the workspace created with the repository.
+const DEFAULT_WORKSPACE_NAME: &str = "default";
+/// The number of characters of change IDs shown, the same Jujutsu shows by default.
+const CHANGE_ID_LENGTH: usize = 8;
+/// The digits Jujutsu uses to encode change IDs, so that they can not be confused with commit
+/// hashes: each hexadecimal digit is mapped to the digit at its index.
+const CHANGE_ID_DIGITS: &[u8; 16] = b"zyxwvutsrqponmlk";
+/// The maximum number of tables of the commit metadata stack read, protecting against cycles.
+const MAXIMUM_TABLE_DEPTH: usize = 64;
+
+/// Represents the backend that reads Jujutsu repositories.
+pub(crate) struct Backend;
+
+impl vcs::Backend for Backend {
+    fn kind(&self) -> Kind {
+        Kind::Jujutsu
+    }
+
+    fn find_root(&self, directory: &Path) -> Option<PathBuf> {
+        vcs::find_root_containing(directory, METADATA_DIRECTORY)
+    }
+
+    fn summarize(&self, root: &Path) -> Option<Summary> {
+        let metadata_directory = root.join(METADATA_DIRECTORY);
+        let repository_directory = find_repository_directory(&metadata_directory)?;
+        let checkout = fs::read(metadata_directory.join("working_copy").join("checkout")).ok()?;
+        let checkout = Message::parse(&checkout)?;
+        let workspace = checkout
+            .string(3)
+            .filter(|workspace| !workspace.is_empty())
+            .unwrap_or(DEFAULT_WORKSPACE_NAME);
+        let view = read_view(&repository_directory, checkout.bytes(2))?;
+        let view = Message::parse(&view)?;
+        let commit_id = view.messages(8).find_map(|entry| {
+            (entry.string(1) == Some(workspace))
+                .then(|| entry.bytes(2))
+                .flatten()
+        })?;
+        let store_directory = repository_directory.join("store");
+        if fs::read_to_string(store_directory.join("type")).is_ok_and(|kind| kind.trim() != "git") {
+            return None;
+        }
+        let git_target = fs::read_to_string(store_directory.join("git_target")).ok()?;
+        let git_repository =
+            git2::Repository::open(store_directory.join(git_target.trim())).ok()?;
+        let commit = git_repository
+            .find_commit(Oid::from_bytes(commit_id).ok()?)
+            .ok()?;
+        let stored = read_stored_commit(&store_directory, commit_id);
+        let stored = stored.as_deref().and_then(Message::parse);
+        let change_id = match commit.header_field_bytes("change-id") {
+            Ok(change_id) => decode_change_id(change_id.as_str()?)?,
+            Err(_) => stored.as_ref()?.bytes(4)?.to_vec(),
+        };
+        let change_id = encode_change_id(&change_id)
+            .chars()
+            .take(CHANGE_ID_LENGTH)
+            .collect::<String>();
+        let is_conflicted = commit
+            .header_field_bytes("jj:trees")
+            .is_ok_and(|trees| trees.split(|byte| *byte == b' ').count() > 1)
+            || stored.is_some_and(|stored| stored.all_bytes(1).count() > 1);
+        let bookmarks = find_bookmarks(&view, &commit);
+        Some(Summary {
+            kind: Kind::Jujutsu,
+            reference: if bookmarks.is_empty() {
+                change_id.clone()
+            } else {
+                bookmarks.join(",")
+            },
+            revision: (!bookmarks.is_empty()).then_some(change_id),
+            is_dirty: is_changed(&commit),
+            is_conflicted,
+        })
+    }
+}
+
+/// Contains the possible results of searching for a commit in a table of the commit metadata stack.
+#[derive(Debug, PartialEq, Eq)]
+enum TableSearch<'a> {
+    /// The metadata of the commit was found.
+    Found(&'a [u8]),
+    /// The commit is not in the table, so it must be searched in its parent, whose name is empty if
+    /// it has none.
+    Parent(&'a str),
+}
+
+/// Contains the fields of a Protocol Buffers message, the format Jujutsu uses to store most of
+/// its metadata. Only length-delimited fields, which hold bytes, strings and embedded messages, are
+/// kept, as no other is needed.
+struct Message<'a> {
+    /// The values of the fields by their number, in the order they are stored.
+    fields: Vec<(u64, &'a [u8])>,
+}
+
+impl<'a> Message<'a> {
+    /// Parses a message from its wire format.
+    ///
+    /// # Parameters
+    /// - `data`: the bytes of the message.
+    ///
+    /// # Returns
+    /// The possible message or `None` if it is malformed.
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        let mut fields = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let key = read_varint(data, &mut offset)?;
+            if key >> 3 == 0 {
+                return None;
+            }
+            match key & 0b111 {
+                0 => {
+                    read_varint(data, &mut offset)?;
+                }
+                1 => offset = offset.checked_add(8)?,
+                2 => {
+                    let length = usize::try_from(read_varint(data, &mut offset)?).ok()?;
+                    fields.push((key >> 3, data.get(offset..offset.checked_add(length)?)?));
+                    offset += length;
+                }
+                5 => offset = offset.checked_add(4)?,
+                _ => return None,
+            }
+        }
+        (offset == data.len()).then_some(Self { fields })
+    }
+
+    /// Gets the values of a field.
+    ///
+    /// # Parameters
+    /// - `number`: the number of the field.
+    ///
+    /// # Returns
+    /// An iterator over the values.
+    fn all_bytes(&self, number: u64) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.fields
+            .iter()
+            .filter(move |(field_number, _)| *field_number == number)
+            .map(|(_, value)| *value)
+    }
+
+    /// Gets the last value of a field, as Protocol Buffers does for fields that are not repeated.
+    ///
+    /// # Parameters
+    /// - `number`: the number of the field.
+    ///
+    /// # Returns
+    /// The possible value.
+    fn bytes(&self, number: u64) -> Option<&'a [u8]> {
+        self.all_bytes(number).last()
+    }
+
+    /// Gets the last value of a field as a string.
+    ///
+    /// # Parameters
+    /// - `number`: the number of the field.
+    ///
+    /// # Returns
+    /// The possible value or `None` if it is not valid UTF-8.
+    fn string(&self, number: u64) -> Option<&'a str> {
+        self.bytes(number)
+            .and_then(|bytes| str::from_utf8(bytes).ok())
+    }
+
+    /// Gets the values of a field as embedded messages, skipping the malformed ones.
+    ///
+    /// # Parameters
+    /// - `number`: the number of the field.
+    ///
+    /// # Returns
+    /// An iterator over the messages.
+    fn messages(&self, number: u64) -> impl Iterator<Item = Message<'a>> + '_ {
+        self.all_bytes(number).filter_map(Message::parse)
+    }
+}
+
+/// Reads a varint of the Protocol Buffers wire format.
+///
+/// # Parameters
+/// - `data`: the bytes to read from.
+/// - `offset`: the offset to start reading at, advanced past the varint.
+///
+/// # Returns
+/// The possible value or `None` if it is truncated or too long.
+fn read_varint(data: &[u8], offset: &mut usize) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*offset)?;
+        *offset += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Finds the directory in which the repository of a workspace is stored. Workspaces other than the
+/// default one store a file with its path instead.
+///
+/// # Parameters
+/// - `metadata_directory`: the full path to the metadata directory of the workspace.
+///
+/// # Returns
+/// The possible full path to the directory.
+fn find_repository_directory(metadata_directory: &Path) -> Option<PathBuf> {
+    let path = metadata_directory.join("repo");
+    if path.is_dir() {
+        return Some(path);
+    }
+    fs::read_to_string(&path)
+        .ok()
+        .map(|target| metadata_directory.join(target.trim()))
+}
+
+/// Reads the view of the repository at its latest operation, which records the working-copy
+/// commits of the workspaces and the bookmarks. If operations have run concurrently, the one the
+/// workspace was last updated at is used instead.
+///
+/// # Parameters
+/// - `repository_directory`: the full path to the directory in which the repository is stored.
+/// - `checkout_operation`: the possible ID of the operation the workspace was last updated at.
+///
+/// # Returns
+/// The possible view data or `None` if it can not be read.
+fn read_view(repository_directory: &Path, checkout_operation: Option<&[u8]>) -> Option<Vec<u8>> {
+    let operation_heads = fs::read_dir(repository_directory.join("op_heads").join("heads"))
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .collect::<Vec<_>>();
+    let operation = match operation_heads.as_slice() {
+        [operation] => operation.clone(),
+        _ => encode_hexadecimal(checkout_operation?),
+    };
+    let operation_store = repository_directory.join("op_store");
+    let operation = fs::read(operation_store.join("operations").join(operation)).ok()?;
+    let view_id = encode_hexadecimal(Message::parse(&operation)?.bytes(1)?);
+    fs::read(operation_store.join("views").join(view_id)).ok()
+}
+
+/// Finds the local bookmarks that point to a commit or, if there are none, to its parents, as
+/// the working-copy commit is usually created on top of a bookmark.
+///
+/// # Parameters
+/// - `view`: the view data of the repository.
+/// - `commit`: the commit.
+///
+/// # Returns
+/// The names of the bookmarks.
+fn find_bookmarks(view: &Message, commit: &Commit) -> Vec<String> {
+    let bookmarks = view
+        .messages(5)
+        .filter_map(|bookmark| {
+            let target = find_target(&Message::parse(bookmark.bytes(2)?)?)?;
+            Some((bookmark.string(1)?.to_string(), target))
+        })
+        .collect::<Vec<_>>();
+    let names_at = |commit_id: Oid| {
+        bookmarks
+            .iter()
+            .filter(|(_, target)| *target == commit_id)
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>()
+    };
+    let names = names_at(commit.id());
+    if !names.is_empty() {
+        return names;
+    }
+    commit.parent_ids().flat_map(names_at).collect()
+}
+
+/// Finds the commit a reference points to. References are stored either with the commit ID alone
+/// or in the form used for conflicts, with the commits added and removed, in which case it must
+/// only add one commit.
+///
+/// # Parameters
+/// - `target`: the target of the reference.
+///
+/// # Returns
+/// The possible commit ID or `None` if the reference is conflicted or has been deleted.
+fn find_target(target: &Message) -> Option<Oid> {
+    if let Some(commit_id) = target.bytes(1) {
+        return Oid::from_bytes(commit_id).ok();
+    }
+    let conflict = Message::parse(target.bytes(3)?)?;
+    if conflict.all_bytes(1).next().is_some() {
+        return None;
+    }
+    match conflict.messages(2).collect::<Vec<_>>().as_slice() {
+        [term] => Oid::from_bytes(term.bytes(1)?).ok(),
+        _ => None,
+    }
+}
+
+/// Reads the metadata Jujutsu stores about a commit that Git can not hold, such as its change
+/// ID and its conflicted trees. It is kept in a stack of tables sorted by commit ID, each one
+/// starting with the name of its parent table, followed by the number of entries, the entries,
+/// each one with its key and the offset of its value, and the values.
+///
+/// # Parameters
+/// - `store_directory`: the full path to the directory in which the commits are stored.
+/// - `commit_id`: the ID of the commit.
+///
+/// # Returns
+/// The possible metadata data or `None` if it can not be found.
+fn read_stored_commit(store_directory: &Path, commit_id: &[u8]) -> Option<Vec<u8>> {
+    let tables_directory = store_directory.join("extra");
+    let mut table_name = fs::read_dir(tables_directory.join("heads"))
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .next();
+    for _ in 0..MAXIMUM_TABLE_DEPTH {
+        let table = fs::read(tables_directory.join(table_name?)).ok()?;
+        match search_table(&table, commit_id)? {
+            TableSearch::Found(metadata) => return Some(metadata.to_vec()),
+            TableSearch::Parent(parent_name) => {
+                table_name = Some(parent_name.to_string()).filter(|name| !name.is_empty());
+            }
+        }
+    }
+    None
+}
+
+/// Searches for a commit in a table of the commit metadata stack, whose entries are sorted by
+/// commit ID.
+///
+/// # Parameters
+/// - `table`: the data of the table.
+/// - `commit_id`: the ID of the commit.
+///
+/// # Returns
+/// The possible result of the search or `None` if the table is malformed.
+fn search_table<'a>(table: &'a [u8], commit_id: &[u8]) -> Option<TableSearch<'a>> {
+    let read_u32 = |offset: usize| -> Option<usize> {
+        let bytes = table.get(offset..offset.checked_add(4)?)?;
+        usize::try_from(u32::from_le_bytes(bytes.try_into().ok()?)).ok()
+    };
+    let parent_name_length = read_u32(0)?;
+    let parent_name = table.get(4..4_usize.checked_add(parent_name_length)?)?;
+    let entries_count = read_u32(4 + parent_name_length)?;
+    let entries_offset = 8 + parent_name_length;
+    let entry_length = commit_id.len() + 4;
+    let values_offset = entries_count
+        .checked_mul(entry_length)?
+        .checked_add(entries_offset)?;
+    let entry_at = |index: usize| entries_offset + index * entry_length;
+    let key_at = |index: usize| table.get(entry_at(index)..entry_at(index) + commit_id.len());
+    let value_at =
+        |index: usize| values_offset.checked_add(read_u32(entry_at(index) + commit_id.len())?);
+    let (mut low, mut high) = (0, entries_count);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match key_at(middle)?.cmp(commit_id) {
+            Ordering::Less => low = middle + 1,
+            Ordering::Greater => high = middle,
+            Ordering::Equal => {
+                let end = if middle + 1 < entries_count {
+                    value_at(middle + 1)?
+                } else {
+                    table.len()
+                };
+                return table.get(value_at(middle)?..end).map(TableSearch::Found);
+            }
+        }
+    }
+    Some(TableSearch::Parent(str::from_utf8(parent_name).ok()?))
+}
+
+/// Checks whether a commit changes any entry compared to its parent, as Jujutsu keeps the changes
+/// of the work tree in the working-copy commit instead.
+///
+/// # Parameters
+/// - `commit`: the commit.
+///
+/// # Returns
+/// A boolean that states that, or `None` if the commit is a merge, as comparing it would require
+/// merging the trees of its parents.
+fn is_changed(commit: &Commit) -> Option<bool> {
+    match commit.parent_count() {
+        0 => Some(commit.tree().is_ok_and(|tree| !tree.is_empty())),
+        1 => Some(commit.parent(0).ok()?.tree_id() != commit.tree_id()),
+        _ => None,
+    }
+}
+
+/// Encodes bytes as hexadecimal digits, the way Jujutsu names the files of its stores.
+///
+/// # Parameters
+/// - `bytes`: the bytes.
+///
+/// # Returns
+/// The digits.
+fn encode_hexadecimal(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decodes a change ID encoded with the digits Jujutsu uses for them, as it is stored in the
+/// headers of Git commits.
+///
+/// # Parameters
+/// - `change_id`: the encoded change ID.
+///
+/// # Returns
+/// The possible bytes of the change ID or `None` if it is malformed.
+fn decode_change_id(change_id: &str) -> Option<Vec<u8>> {
+    let digit = |character: u8| {
+        CHANGE_ID_DIGITS
+            .iter()
+            .position(|digit| *digit == character)
+            .and_then(|digit| u8::try_from(digit).ok())
+    };
+    change_id
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => Some(digit(*high)? << 4 | digit(*low)?),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Encodes a change ID with the digits Jujutsu uses for them.
+///
+/// # Parameters
+/// - `change_id`: the bytes of the change ID.
+///
+/// # Returns
+/// The encoded change ID.
+fn encode_change_id(change_id: &[u8]) -> String {
+    change_id
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xf])
+        .map(|digit| char::from(CHANGE_ID_DIGITS[usize::from(digit)]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes a length-delimited field.
+    fn field(number: u8, value: &[u8]) -> Vec<u8> {
+        [&[number << 3 | 2, value.len() as u8][..], value].concat()
+    }
+
+    /// Encodes a table of the commit metadata stack with entries sorted by key.
+    fn encode_table(parent_name: &str, entries: &[(&[u8], &[u8])]) -> Vec<u8> {
+        let mut table = (parent_name.len() as u32).to_le_bytes().to_vec();
+        table.extend(parent_name.as_bytes());
+        table.extend((entries.len() as u32).to_le_bytes());
+        let mut offset = 0_u32;
+        for (key, value) in entries {
+            table.extend(*key);
+            table.extend(offset.to_le_bytes());
+            offset += value.len() as u32;
+        }
+        for (_, value) in entries {
+            table.extend(*value);
+        }
+        table
+    }
+
+    #[test]
+    fn varints_are_read() {
+        let mut offset = 0;
+        assert_eq!(read_varint(&[0x01, 0xac, 0x02], &mut offset), Some(1));
+        assert_eq!(read_varint(&[0x01, 0xac, 0x02], &mut offset), Some(300));
+        assert_eq!(offset, 3);
+    }
+
+    #[test]
+    fn malformed_varints_are_rejected() {
+        assert_eq!(read_varint(&[], &mut 0), None);
+        assert_eq!(read_varint(&[0x80, 0x80], &mut 0), None);
+        assert_eq!(read_varint(&[0xff; 11], &mut 0), None);
+    }
+
+    #[test]
+    fn unknown_fields_are_skipped() {
+        let data = [
+            &[0x08, 0x96, 0x01][..],
+            &[0x11, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[0x1d, 0, 0, 0, 0],
+            &field(4, b"first"),
+            &field(4, b"last"),
+        ]
+        .concat();
+        let message = Message::parse(&data).unwrap();
+        assert_eq!(message.bytes(1), None);
+        assert_eq!(
+            message.all_bytes(4).collect::<Vec<_>>(),
+            [&b"first"[..], b"last"]
+        );
+        assert_eq!(message.string(4), Some("last"));
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        for wire_type in [3, 4, 6, 7] {
+            assert!(Message::parse(&[1 << 3 | wire_type, 0]).is_none());
+        }
+        assert!(Message::parse(&[0x02, 0]).is_none());
+        assert!(Message::parse(&[0x0a, 5, b'a']).is_none());
+        assert!(Message::parse(&[0x0a]).is_none());
+        assert!(Message::parse(&[0x11, 0, 0, 0]).is_none());
+        assert!(Message::parse(&[0x1d, 0]).is_none());
+        assert!(Message::parse(&[0x08]).is_none());
+        assert!(Message::parse(&[0x0a, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]).is_none());
+    }
+
+    #[test]
+    fn malformed_embedded_messages_are_skipped() {
+        let data = [field(1, &field(2, b"valid")), field(1, &[0x0a, 5])].concat();
+        let message = Message::parse(&data).unwrap();
+        let embedded = message.messages(1).collect::<Vec<_>>();
+        assert_eq!(embedded.len(), 1);
+        assert_eq!(embedded[0].bytes(2), Some(&b"valid"[..]));
+    }
+
+    #[test]
+    fn targets_are_found() {
+        let commit_id = [1; 20];
+        let added = field(2, &field(1, &commit_id));
+        let removed = field(1, &field(1, &[2; 20]));
+        let target = |data: &[u8]| find_target(&Message::parse(data).unwrap());
+        assert_eq!(
+            target(&field(1, &commit_id)),
+            Oid::from_bytes(&commit_id).ok()
+        );
+        assert_eq!(target(&field(1, &commit_id[..19])), None);
+        assert_eq!(target(&field(3, &added)), Oid::from_bytes(&commit_id).ok());
+        assert_eq!(
+            target(&field(3, &[added.clone(), added.clone()].concat())),
+            None
+        );
+        assert_eq!(target(&field(3, &[removed, added].concat())), None);
+        assert_eq!(target(&[]), None);
+    }
+
+    #[test]
+    fn tables_are_searched() {
+        let table = encode_table(
+            "parent",
+            &[(b"aa", b"first"), (b"bb", b""), (b"cc", b"last")],
+        );
+        assert_eq!(
+            search_table(&table, b"aa"),
+            Some(TableSearch::Found(b"first"))
+        );
+        assert_eq!(search_table(&table, b"bb"), Some(TableSearch::Found(b"")));
+        assert_eq!(
+            search_table(&table, b"cc"),
+            Some(TableSearch::Found(b"last"))
+        );
+        assert_eq!(
+            search_table(&table, b"ab"),
+            Some(TableSearch::Parent("parent"))
+        );
+        let empty = encode_table("", &[]);
+        assert_eq!(search_table(&empty, b"aa"), Some(TableSearch::Parent("")));
+    }
+
+    #[test]
+    fn malformed_tables_are_rejected() {
+        let table = encode_table("parent", &[(b"aa", b"first"), (b"cc", b"last")]);
+        for length in 0..table.len() - b"firstlast".len() {
+            assert_eq!(search_table(&table[..length], b"cc"), None);
+        }
+        let mut unordered = table.clone();
+        let second_offset = 4 + 6 + 4 + 6 + 2;
+        unordered[second_offset..second_offset + 4].copy_from_slice(&100_u32.to_le_bytes());
+        assert_eq!(search_table(&unordered, b"aa"), None);
+        let mut oversized = table;
+        oversized[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(search_table(&oversized, b"bb"), None);
+    }
+
+    #[test]
+    fn change_ids_are_encoded_and_decoded() {
+        assert_eq!(encode_change_id(&[0x01, 0x23, 0xef]), "zyxwlk");
+        assert_eq!(decode_change_id("zyxwlk"), Some(vec![0x01, 0x23, 0xef]));
+        assert_eq!(decode_change_id("zyx"), None);
+        assert_eq!(decode_change_id("zyab"), None);
+        assert_eq!(encode_hexadecimal(&[0x01, 0xab]), "01ab");
+    }
+}
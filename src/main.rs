@@ -1,14 +1,20 @@
-//! A tribal looking ZSH shell theme made to help you craft your most ambitious software projects.
+//! A tribal looking ZSH, Bash, Fish and Nushell shell theme made to help you craft your most
+//! ambitious software projects.
 //! It is available for macOS and Linux.
 
 pub(crate) mod command_line;
+pub(crate) mod config;
+pub(crate) mod daemon;
 pub(crate) mod error;
 pub(crate) mod file_system;
 pub(crate) mod format;
 pub(crate) mod git;
 pub(crate) mod hardware;
+pub(crate) mod jujutsu;
+pub(crate) mod mercurial;
 pub(crate) mod metadata;
 pub(crate) mod prompt;
+pub(crate) mod vcs;
 pub(crate) mod datetime;
 
 use std::env;
@@ -16,28 +22,62 @@ use std::process::ExitCode;
 
 use anyhow::anyhow;
 
-use crate::command_line::{ArgumentParsing as _, Command};
+use crate::command_line::{ArgumentParsing as _, Command, PromptOption};
+use crate::config::SectionId;
+use crate::prompt::Context;
+use crate::prompt::shell::{Shell, State};
 
-/// The entry point of the application.
+/// The entry point of the application. Prompts are requested to the daemon, if it is running.
 fn main() -> ExitCode {
     let arguments = env::args().skip(1).collect::<Vec<_>>();
+    if arguments
+        .first()
+        .is_some_and(|argument| argument == Command::Prompt.name())
+        && let Some(exit_code) = daemon::request(&arguments)
+    {
+        return exit_code;
+    }
+    run(&arguments)
+}
+
+/// Runs the application.
+///
+/// # Parameters
+/// - `arguments`: the command-line arguments to be considered, without the application name.
+///
+/// # Returns
+/// The exit code of the application.
+fn run(arguments: &[String]) -> ExitCode {
     let mut command = None;
     if let Some(command_argument) = arguments.first() {
         if command_argument == Command::Init.name() {
             command = Some(Command::Init);
         } else if command_argument == Command::Prompt.name() {
             command = Some(Command::Prompt);
+        } else if command_argument == Command::GitStatus.name() {
+            command = Some(Command::GitStatus);
+        } else if command_argument == Command::Daemon.name() {
+            command = Some(Command::Daemon);
         } else if !command_argument.is_option() {
             error::write(anyhow!(r#"invalid command "{command_argument}" provided."#));
             return ExitCode::FAILURE;
         }
     }
-    for argument in &arguments {
+    let mut values = Vec::new();
+    let mut shell_name = None;
+    let mut exit_code = None;
+    let mut jobs_count = None;
+    let mut git_status = None;
+    let mut command_duration = None;
+    let mut arguments_iterator = arguments.iter().skip(usize::from(command.is_some()));
+    while let Some(argument) = arguments_iterator.next() {
         if argument == "-h" || argument == "--help" {
             return match match command {
                 None => metadata::write_main_help(),
                 Some(Command::Prompt) => metadata::write_prompt_command_help(),
                 Some(Command::Init) => metadata::write_init_command_help(),
+                Some(Command::GitStatus) => metadata::write_git_status_command_help(),
+                Some(Command::Daemon) => metadata::write_daemon_command_help(),
             } {
                 Ok(_) => ExitCode::SUCCESS,
                 Err(error) => {
@@ -46,22 +86,41 @@ fn main() -> ExitCode {
                 }
             };
         }
-        if command.is_none() {
-            if let Some(action) = match argument.as_str() {
+        if command.is_none()
+            && let Some(action) = match argument.as_str() {
                 "-v" | "--version" => Some(metadata::write_version()),
                 "-g" | "--repository" => Some(metadata::open_repository()),
                 "-m" | "--email" => Some(metadata::draft_email_to_developer()),
                 "-l" | "--license" => Some(metadata::write_license()),
                 _ => None,
-            } {
-                return match action {
-                    Ok(_) => ExitCode::SUCCESS,
-                    Err(error) => {
-                        error::write(error);
-                        ExitCode::FAILURE
-                    }
-                };
             }
+        {
+            return match action {
+                Ok(_) => ExitCode::SUCCESS,
+                Err(error) => {
+                    error::write(error);
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        if command == Some(Command::Prompt)
+            && let Some(option) = PromptOption::from_argument(argument)
+        {
+            let value = match arguments_iterator.next() {
+                Some(value) => value.as_str(),
+                None => {
+                    error::write(anyhow!(r#"no value provided for option "{argument}"."#));
+                    return ExitCode::FAILURE;
+                }
+            };
+            match option {
+                PromptOption::Shell => shell_name = Some(value),
+                PromptOption::ExitCode => exit_code = Some(value),
+                PromptOption::JobsCount => jobs_count = Some(value),
+                PromptOption::GitStatus => git_status = Some(value),
+                PromptOption::CommandDuration => command_duration = Some(value),
+            }
+            continue;
         }
         if argument.is_option() {
             error::write(if let Some(command) = command {
@@ -74,24 +133,45 @@ fn main() -> ExitCode {
             });
             return ExitCode::FAILURE;
         }
+        values.push(argument.as_str());
     }
     if let Err(error) = match command {
         None => Err(anyhow!("no command provided.")),
         Some(Command::Prompt) => {
-            if arguments.len() == 1 {
-                error::write(anyhow!("no prompt side provided."));
-                return ExitCode::FAILURE;
-            }
-            match arguments[1].as_str() {
-                "l" | "left" => prompt::left::write(),
-                "r" | "right" => prompt::right::write(),
-                _ => Err(anyhow!(
-                    r#"invalid prompt side "{}" provided."#,
-                    arguments[1]
-                )),
-            }
+            let side = match values.first() {
+                Some(side) => *side,
+                None => {
+                    error::write(anyhow!("no prompt side provided."));
+                    return ExitCode::FAILURE;
+                }
+            };
+            State::parse(exit_code, jobs_count)
+                .and_then(|state| Shell::new(shell_name.unwrap_or(Shell::Zsh.name()), state))
+                .and_then(|shell| {
+                    config::load().and_then(|config| {
+                        Context::parse(
+                            shell,
+                            git_status,
+                            command_duration,
+                            config.section(SectionId::Git).diff_limit(),
+                        )
+                        .and_then(|context| match side {
+                            "l" | "left" => prompt::left::write(&config, context),
+                            "r" | "right" => prompt::right::write(&config, context),
+                            _ => Err(anyhow!(r#"invalid prompt side "{side}" provided."#)),
+                        })
+                    })
+                })
         }
-        Some(Command::Init) => prompt::init(),
+        Some(Command::Init) => Shell::new(
+            values.first().copied().unwrap_or(Shell::Zsh.name()),
+            State::default(),
+        )
+        .and_then(|shell| prompt::init(&shell)),
+        Some(Command::GitStatus) => config::load().and_then(|config| {
+            prompt::write_git_work_tree_state(config.section(SectionId::Git).diff_limit())
+        }),
+        Some(Command::Daemon) => daemon::serve(run),
     } {
         error::write(error);
         return ExitCode::FAILURE;
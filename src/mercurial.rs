@@ -0,0 +1,255 @@
+//! Provides features to retrieve Mercurial repositories metadata by reading their files directly,
+//! without running the `hg` command.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::{Path, PathBuf};
+
+use crate::vcs::{self, Kind, Summary};
+
+/// The name of the directory in which Mercurial stores the metadata of a repository.
+const METADATA_DIRECTORY: &str = ".hg";
+/// The branch name used by Mercurial when none has been set.
+const DEFAULT_BRANCH_NAME: &str = "default";
+/// The length in bytes of the header of the dirstate file, which contains the hashes of the two
+/// parents of the work tree.
+const DIRSTATE_HEADER_LENGTH: usize = 40;
+/// The length in bytes of the fixed part of each dirstate entry: its state, mode, size, modification
+/// time and the length of its name.
+const DIRSTATE_ENTRY_LENGTH: usize = 17;
+/// The mask Mercurial applies to sizes and modification times stored in the dirstate file.
+const DIRSTATE_VALUE_MASK: u64 = 0x7fff_ffff;
+
+/// Represents the backend that reads Mercurial repositories.
+pub(crate) struct Backend;
+
+impl vcs::Backend for Backend {
+    fn kind(&self) -> Kind {
+        Kind::Mercurial
+    }
+
+    fn find_root(&self, directory: &Path) -> Option<PathBuf> {
+        vcs::find_root_containing(directory, METADATA_DIRECTORY)
+    }
+
+    fn summarize(&self, root: &Path) -> Option<Summary> {
+        let metadata_directory = root.join(METADATA_DIRECTORY);
+        let reference = read_name(&metadata_directory.join("bookmarks.current"))
+            .or_else(|| read_name(&metadata_directory.join("branch")))
+            .unwrap_or_else(|| DEFAULT_BRANCH_NAME.to_string());
+        Some(Summary {
+            kind: Kind::Mercurial,
+            reference,
+            revision: None,
+            is_dirty: is_dirty(root),
+            is_conflicted: false,
+        })
+    }
+}
+
+/// Reads a name stored alone in a file, e.g. the active bookmark.
+///
+/// # Parameters
+/// - `path`: the path to the file.
+///
+/// # Returns
+/// The possible name or `None` if the file does not exist or is empty.
+fn read_name(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Checks whether the work tree of a Mercurial repository contains uncommited changes by reading its
+/// dirstate file: entries added, removed, merged or missing, entries whose size differ from the one
+/// recorded, and uncommited merges are considered. Entries whose modification time differ from the
+/// one recorded are considered clean, as Mercurial itself only takes those as a hint to compare their
+/// contents with the ones committed, which can not be read without decompressing its revlogs.
+/// Untracked entries are not considered either, as finding them requires evaluating the ignore
+/// rules.
+///
+/// # Parameters
+/// - `root`: the full path to the root directory of the repository.
+///
+/// # Returns
+/// A boolean that states that, or `None` if the dirstate can not be read, e.g. because it uses the
+/// second version of its format.
+fn is_dirty(root: &Path) -> Option<bool> {
+    let metadata_directory = root.join(METADATA_DIRECTORY);
+    if fs::read_to_string(metadata_directory.join("requires"))
+        .is_ok_and(|requirements| requirements.lines().any(|line| line == "dirstate-v2"))
+    {
+        return None;
+    }
+    match fs::read(metadata_directory.join("dirstate")) {
+        Ok(dirstate) => is_dirstate_dirty(root, &dirstate),
+        Err(_) => Some(false),
+    }
+}
+
+/// Checks whether the entries of a dirstate file describe a work tree with uncommited changes.
+///
+/// # Parameters
+/// - `root`: the full path to the root directory of the repository, which the names of the entries
+///   are relative to.
+/// - `dirstate`: the contents of the dirstate file, in the first version of its format.
+///
+/// # Returns
+/// A boolean that states that, or `None` if the contents are malformed.
+fn is_dirstate_dirty(root: &Path, dirstate: &[u8]) -> Option<bool> {
+    if dirstate.is_empty() {
+        return Some(false);
+    }
+    let second_parent = dirstate.get(DIRSTATE_HEADER_LENGTH / 2..DIRSTATE_HEADER_LENGTH)?;
+    if second_parent.iter().any(|byte| *byte != 0) {
+        return Some(true);
+    }
+    let mut offset = DIRSTATE_HEADER_LENGTH;
+    while offset < dirstate.len() {
+        let entry = dirstate.get(offset..offset + DIRSTATE_ENTRY_LENGTH)?;
+        let field = |index: usize| entry[index..index + 4].try_into().map(i32::from_be_bytes);
+        let size = field(5).ok()?;
+        let name_length = usize::try_from(field(13).ok()?).ok()?;
+        offset += DIRSTATE_ENTRY_LENGTH;
+        let name = dirstate.get(offset..offset + name_length)?;
+        offset += name_length;
+        if entry[0] != b'n' {
+            return Some(true);
+        }
+        let name = name.split(|byte| *byte == 0).next().unwrap_or(name);
+        let metadata = match fs::symlink_metadata(root.join(OsStr::from_bytes(name))) {
+            Ok(metadata) => metadata,
+            Err(_) => return Some(true),
+        };
+        if size >= 0 && (metadata.len() & DIRSTATE_VALUE_MASK) as i32 != size {
+            return Some(true);
+        }
+    }
+    Some(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::ops::Deref;
+    use std::process;
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    /// Represents an empty directory used as the root of a repository, removed when dropped.
+    struct Root(PathBuf);
+
+    impl Deref for Root {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for Root {
+        fn drop(&mut self) {
+            _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Creates an empty directory to be used as the root of a repository.
+    fn create_root(name: &str) -> Root {
+        let root = env::temp_dir().join(format!("river_dreams-test-{}-{name}", process::id()));
+        _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        Root(root)
+    }
+
+    /// Encodes a dirstate header with the given second parent.
+    fn header(second_parent: u8) -> Vec<u8> {
+        let mut header = vec![1; DIRSTATE_HEADER_LENGTH / 2];
+        header.extend([second_parent; DIRSTATE_HEADER_LENGTH / 2]);
+        header
+    }
+
+    /// Encodes a dirstate entry.
+    fn entry(state: u8, size: i32, modified_time: i32, name: &[u8]) -> Vec<u8> {
+        let mut entry = vec![state];
+        entry.extend(0o100644_i32.to_be_bytes());
+        entry.extend(size.to_be_bytes());
+        entry.extend(modified_time.to_be_bytes());
+        entry.extend((name.len() as i32).to_be_bytes());
+        entry.extend(name);
+        entry
+    }
+
+    /// Gets the modification time of a file as stored in the dirstate.
+    fn modified_time(path: &Path) -> i32 {
+        let time = fs::metadata(path)
+            .unwrap()
+            .modified()
+            .unwrap()
+            .duration_since(UNIX_EPOCH)
+            .unwrap();
+        (time.as_secs() & DIRSTATE_VALUE_MASK) as i32
+    }
+
+    #[test]
+    fn empty_dirstate_is_clean() {
+        let root = create_root("empty");
+        assert_eq!(is_dirstate_dirty(&root, &[]), Some(false));
+        assert_eq!(is_dirstate_dirty(&root, &header(0)), Some(false));
+    }
+
+    #[test]
+    fn truncated_dirstate_is_unknown() {
+        let root = create_root("truncated");
+        assert_eq!(is_dirstate_dirty(&root, &header(0)[..30]), None);
+        let dirstate = [header(0), entry(b'a', 0, 0, b"file")].concat();
+        for length in DIRSTATE_HEADER_LENGTH + 1..dirstate.len() {
+            assert_eq!(is_dirstate_dirty(&root, &dirstate[..length]), None);
+        }
+    }
+
+    #[test]
+    fn negative_name_length_is_unknown() {
+        let root = create_root("negative");
+        let mut dirstate = [header(0), entry(b'n', 0, 0, b"")].concat();
+        let name_length = DIRSTATE_HEADER_LENGTH + 13;
+        dirstate[name_length..name_length + 4].copy_from_slice(&(-1_i32).to_be_bytes());
+        assert_eq!(is_dirstate_dirty(&root, &dirstate), None);
+    }
+
+    #[test]
+    fn uncommited_merge_is_dirty() {
+        let root = create_root("merge");
+        assert_eq!(is_dirstate_dirty(&root, &header(1)), Some(true));
+    }
+
+    #[test]
+    fn entries_not_in_normal_state_are_dirty() {
+        let root = create_root("states");
+        for state in [b'a', b'r', b'm'] {
+            let dirstate = [header(0), entry(state, 0, 0, b"file")].concat();
+            assert_eq!(is_dirstate_dirty(&root, &dirstate), Some(true));
+        }
+    }
+
+    #[test]
+    fn normal_entries_are_compared_with_the_work_tree() {
+        let root = create_root("normal");
+        fs::write(root.join("file"), "data").unwrap();
+        let time = modified_time(&root.join("file"));
+        let unchanged = [header(0), entry(b'n', 4, time, b"file")].concat();
+        assert_eq!(is_dirstate_dirty(&root, &unchanged), Some(false));
+        let resized = [header(0), entry(b'n', 3, time, b"file")].concat();
+        assert_eq!(is_dirstate_dirty(&root, &resized), Some(true));
+        let touched = [header(0), entry(b'n', 4, time - 1, b"file")].concat();
+        assert_eq!(is_dirstate_dirty(&root, &touched), Some(false));
+        let unset = [header(0), entry(b'n', -1, -1, b"file")].concat();
+        assert_eq!(is_dirstate_dirty(&root, &unset), Some(false));
+        let copied = [header(0), entry(b'n', 4, time, b"file\0source")].concat();
+        assert_eq!(is_dirstate_dirty(&root, &copied), Some(false));
+        let missing = [header(0), entry(b'n', 4, time, b"other")].concat();
+        assert_eq!(is_dirstate_dirty(&root, &missing), Some(true));
+    }
+}
@@ -99,8 +99,10 @@ pub(crate) fn write_main_help() -> Result<()> {
 Performs actions related to the River Dreams theme.
 
 {}
-    {}  writes a prompt side using ZSH syntax.
-    {}    dumps the ZSH script that initiates the prompt.
+    {}      writes a prompt side using the syntax of a shell.
+    {}        dumps the script that initiates the prompt in a shell.
+    {}  writes the changes of the current Git repository.
+    {}      serves prompts from a long-lived process to speed them up.
 
 {} use {} or {} with each for their help instructions.
 
@@ -121,6 +123,8 @@ Performs actions related to the River Dreams theme.
         "❡ AVAILABLE COMMANDS".dark_magenta().bold(),
         "prompt".dark_yellow(),
         "init".dark_yellow(),
+        "git-status".dark_yellow(),
+        "daemon".dark_yellow(),
         " INFO:".dark_cyan().bold(),
         "-h".dark_cyan(),
         "--help".dark_cyan(),
@@ -150,7 +154,7 @@ pub(crate) fn write_prompt_command_help() -> Result<()> {
     stdout_write!(
         &mut stdout,
         "{}{}{} {} {} prompt <{}> [{}]...
-Writes a prompt side using ZSH syntax.
+Writes a prompt side using the syntax of a shell.
 
 Its outputs are used during initialization to write the prompt.
 
@@ -163,7 +167,13 @@ For more information, use:
     {}, {}  writes the right prompt.
 
 {}
-    {}, {}  shows the command help instructions.
+    {}, {}                shows the command help instructions.
+    {}, {} <{}>       uses the syntax of a shell: zsh (default), bash, fish or nu.
+    {}, {} <{}>    sets the exit code of the last command, used by all but zsh.
+    {}, {} <{}>        sets the total of background jobs, used by all but zsh.
+    {}, {} <{}>  sets the Git changes computed in the background, as written
+                              by git-status, or pending. If not set, they are computed.
+    {}, {} <{}>       sets the duration of the last command in milliseconds.
 ",
         ":".dark_yellow().bold(),
         "<>".dark_red().bold(),
@@ -180,7 +190,22 @@ For more information, use:
         "right".dark_yellow(),
         "❡ AVAILABLE OPTIONS".dark_magenta().bold(),
         "-h".dark_cyan(),
-        "--help".dark_cyan()
+        "--help".dark_cyan(),
+        "-s".dark_cyan(),
+        "--shell".dark_cyan(),
+        "SHELL".dark_yellow().underlined(),
+        "-e".dark_cyan(),
+        "--exit-code".dark_cyan(),
+        "CODE".dark_yellow().underlined(),
+        "-j".dark_cyan(),
+        "--jobs".dark_cyan(),
+        "COUNT".dark_yellow().underlined(),
+        "-g".dark_cyan(),
+        "--git-status".dark_cyan(),
+        "STATE".dark_yellow().underlined(),
+        "-d".dark_cyan(),
+        "--duration".dark_cyan(),
+        "MS".dark_yellow().underlined()
     )
 }
 
@@ -195,14 +220,117 @@ pub(crate) fn write_init_command_help() -> Result<()> {
     let mut stdout = io::stdout().lock();
     stdout_write!(
         &mut stdout,
-        "{}{}{} {} {} init [{}]...
-Dumps the ZSH script that initiates the prompt.
+        "{}{}{} {} {} init [{}] [{}]...
+Dumps the script that initiates the prompt in a shell.
 
-Its output is meant to be executed during the ZSH startup, by adding:
+Its output is meant to be executed during the shell startup, by adding:
 
     eval $({} init);
 
-to your ~/.zshrc configuration file.
+to your ~/.zshrc configuration file, or:
+
+    eval \"$({} init bash)\";
+
+to your ~/.bashrc configuration file, or:
+
+    {} init fish | source;
+
+to your ~/.config/fish/config.fish configuration file, or by saving it to the Nushell autoload
+directory:
+
+    {} init nu | save -f ($nu.data-dir | path join \"vendor/autoload/river_dreams.nu\");
+
+{}
+    {}   the default shell.
+    {}  requires version 4.4 or later, or 5.0 to show command durations.
+    {}  requires version 3.0 or later.
+    {}    requires version 0.103 or later.
+
+{}
+    {}, {}  shows the command help instructions.
+",
+        ":".dark_yellow().bold(),
+        "<>".dark_red().bold(),
+        "::".dark_yellow().bold(),
+        "Usage:".dark_magenta().bold(),
+        APP_METADATA.name,
+        "SHELL".dark_yellow().underlined(),
+        "OPTIONS".dark_cyan().underlined(),
+        APP_METADATA.name,
+        APP_METADATA.name,
+        APP_METADATA.name,
+        APP_METADATA.name,
+        "❡ AVAILABLE SHELLS".dark_magenta().bold(),
+        "zsh".dark_yellow(),
+        "bash".dark_yellow(),
+        "fish".dark_yellow(),
+        "nu".dark_yellow(),
+        "❡ AVAILABLE OPTIONS".dark_magenta().bold(),
+        "-h".dark_cyan(),
+        "--help".dark_cyan()
+    )
+}
+
+/// Writes the application git-status command help to the terminal output stream.
+///
+/// # Returns
+/// A possible error.
+///
+/// # Errors
+/// It returns a displayable error if it cannot write to the stream.
+pub(crate) fn write_git_status_command_help() -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout_write!(
+        &mut stdout,
+        "{}{}{} {} {} git-status [{}]...
+Writes the changes of the current Git repository: clean, or dirty followed by the number of staged,
+modified, untracked, renamed and conflicted entries, and of dirty submodules, plus the number of
+lines inserted and deleted if there are not too many changes, e.g. dirty:1,2,0,0,0,0:10,3. If the
+repository sets river-dreams.skipDirtyCheck in its Git configuration, unknown is written instead.
+
+How entries are scanned follows the status.showUntrackedFiles and diff.ignoreSubmodules keys of the
+Git configuration. The core.untrackedCache key is not honored yet, as libgit2 can not read the
+untracked cache and filling it would require writing the index, locking it while other Git commands
+run.
+
+It is run in the background by the ZSH prompt, so that large repositories do not delay it. Nothing
+is written outside of a repository.
+
+{}
+    {}, {}  shows the command help instructions.
+",
+        ":".dark_yellow().bold(),
+        "<>".dark_red().bold(),
+        "::".dark_yellow().bold(),
+        "Usage:".dark_magenta().bold(),
+        APP_METADATA.name,
+        "OPTIONS".dark_cyan().underlined(),
+        "❡ AVAILABLE OPTIONS".dark_magenta().bold(),
+        "-h".dark_cyan(),
+        "--help".dark_cyan()
+    )
+}
+
+/// Writes the application daemon command help to the terminal output stream.
+///
+/// # Returns
+/// A possible error.
+///
+/// # Errors
+/// It returns a displayable error if it cannot write to the stream.
+pub(crate) fn write_daemon_command_help() -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout_write!(
+        &mut stdout,
+        "{}{}{} {} {} daemon [{}]...
+Serves prompts from a long-lived process through a per-user Unix socket.
+
+While it runs, the prompt command requests to it instead of rendering by itself, avoiding the
+startup cost of the application and reusing the hardware metadata it keeps cached. If it is not
+running, prompts are rendered as usual. It can be started in the background during the shell
+startup, for example:
+
+    {} daemon 2> /dev/null &!
 
 {}
     {}, {}  shows the command help instructions.
@@ -3,28 +3,32 @@
 use std::env;
 use std::io::{self, StdoutLock};
 use std::net::IpAddr;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Local};
-use crossterm::terminal;
 
 use crate::command_line::stdout_write;
+use crate::config::{Config, Line, SectionConfig, Truncation};
 use crate::file_system::directory;
 use crate::file_system::path::PathResolutions as _;
 use crate::hardware::{battery, disk};
-use crate::prompt::{self, Color, ZSH_EXIT_CODE, ZSH_PERCENTAGE_SYMBOL};
+use crate::prompt::section::{self, Registry, Section};
+use crate::prompt::shell::Shell;
+use crate::prompt::symbols::Symbols;
+use crate::prompt::{self, Color, Context};
 use crate::datetime::{DateTimeResolutions as _, DayFraction};
-use crate::{format, git};
-
-/// The length of the prompt that is composed by constant section areas.
-const SECTIONS_CONSTANT_LENGTH: prompt::Size = 42;
+use crate::{format, git, vcs};
 
 /// Writes the prompt separator composed by the first tribal symbol set seen at its top to the
 /// terminal output stream.
 ///
 /// # Parameters
 /// - `stdout`: the mutex lock of the stream.
+/// - `shell`: the shell whose syntax is used.
+/// - `symbols`: the symbols to be used.
 /// - `terminal_width`: the terminal width to be considered.
 ///
 /// # Returns
@@ -35,188 +39,379 @@ const SECTIONS_CONSTANT_LENGTH: prompt::Size = 42;
 ///
 /// # Panics
 /// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_top_separator(stdout: &mut StdoutLock, terminal_width: prompt::Size) -> Result<()> {
+fn write_top_separator(
+    stdout: &mut StdoutLock,
+    shell: &Shell,
+    symbols: &Symbols,
+    terminal_width: prompt::Size,
+) -> Result<()> {
     for column in 0..terminal_width {
         stdout_write!(
             stdout,
             "{}",
             if column % 2 == 0 {
-                prompt::color_symbol("≥", Color::Yellow)
+                shell.color_symbol(symbols.top_separator[0], Color::Yellow)
             } else {
-                prompt::color_symbol("v", Color::Red)
+                shell.color_symbol(symbols.top_separator[1], Color::Red)
             }
         )?;
     }
-    stdout_write!(stdout, "{}", prompt::color_symbol(":«(", Color::Yellow))
+    stdout_write!(
+        stdout,
+        "{}{}",
+        shell.line_break(),
+        shell.color_symbol(symbols.upper_line_decorators[0], Color::Yellow)
+    )
 }
 
-/// Writes the prompt section that shows the primary local IP address of the machine to the terminal
-/// output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `ip`: the possible IP to be considered. If `None`, the function will not do anything.
-/// - `sections_length`: a reference to the current prompt length. It gets incremented by this
-/// section length upon a complete execution.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns a displayable error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_local_ip_section(
-    stdout: &mut StdoutLock,
+/// Represents the prompt section that shows the primary local IP address of the machine.
+pub(super) struct LocalIpSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The possible IP collected. If `None`, a placeholder is shown instead.
     ip: Option<IpAddr>,
-    sections_length: &mut prompt::Size,
-) -> Result<()> {
-    let ip = ip
-        .map(|ip| ip.to_string())
-        .unwrap_or(String::from("No Address Found"));
-    stdout_write!(stdout, "{} {}", prompt::color_symbol(" ", Color::Blue), ip)?;
-    *sections_length += ip.len() as prompt::Size;
-    Ok(())
 }
 
-/// Writes the prompt section that shows the disk usage and its status to the terminal output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `usage`: the usage to be considered.
-/// - `sections_length`: a reference to the current prompt length. It gets incremented by this
-/// section length upon a complete execution.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_disk_section(
-    stdout: &mut StdoutLock,
-    usage: disk::Usage,
-    sections_length: &mut prompt::Size,
-) -> Result<()> {
-    stdout_write!(
-        stdout,
-        "  {}{}{}",
-        prompt::color_symbol(
-            "󰋊 ",
-            match usage.status() {
-                disk::UsageStatus::Low => Color::Green,
-                disk::UsageStatus::Moderate => Color::Yellow,
-                disk::UsageStatus::High => Color::Red,
-            }
-        ),
-        usage.0,
-        ZSH_PERCENTAGE_SYMBOL
-    )?;
-    *sections_length += format::number_length(usage.0)? as prompt::Size;
-    Ok(())
+impl LocalIpSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            ip: None,
+        }
+    }
+
+    /// Allocates a string on the heap containing the IP to be shown.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    fn ip(&self) -> String {
+        self.ip
+            .map(|ip| ip.to_string())
+            .unwrap_or(String::from("No Address Found"))
+    }
 }
 
-/// Writes the prompt section that shows the battery charge and its status to the terminal output
-/// stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `charge`: the possible charge to be considered. If `None`, the function will not do anything.
-/// - `sections_length`: a reference to the current prompt length. It gets incremented by this
-/// section length upon a complete execution.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_battery_section(
-    stdout: &mut StdoutLock,
+impl Section for LocalIpSection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        self.ip = context.local_ip();
+        Ok(())
+    }
+
+    fn width(&self) -> prompt::Size {
+        prompt::width(self.settings.symbol(self.symbols.local_ip)) + 1 + prompt::width(self.ip())
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        Ok(format!(
+            "{} {}",
+            shell.color_symbol(
+                self.settings.symbol(self.symbols.local_ip),
+                self.settings.color(Color::Blue)
+            ),
+            self.ip()
+        ))
+    }
+}
+
+/// Represents the prompt section that shows the disk usage and its status.
+pub(super) struct DiskSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The usage collected.
+    usage: Option<disk::Usage>,
+}
+
+impl DiskSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            usage: None,
+        }
+    }
+}
+
+impl Section for DiskSection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        self.usage = Some(context.disk_usage()?);
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.usage.is_some()
+    }
+
+    fn width(&self) -> prompt::Size {
+        self.usage.map_or(0, |usage| {
+            prompt::width(self.settings.symbol(self.symbols.disk(usage.status())))
+                + format::number_length(usage.0).unwrap_or_default() as prompt::Size
+                + 1
+        })
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let usage = match self.usage {
+            Some(usage) => usage,
+            None => return Ok(String::new()),
+        };
+        Ok(format!(
+            "{}{}{}",
+            shell.color_symbol(
+                self.settings.symbol(self.symbols.disk(usage.status())),
+                self.settings.color(match usage.status() {
+                    disk::UsageStatus::Low => Color::Green,
+                    disk::UsageStatus::Moderate => Color::Yellow,
+                    disk::UsageStatus::High => Color::Red,
+                })
+            ),
+            usage.0,
+            shell.escape("%")
+        ))
+    }
+}
+
+/// Represents the prompt section that shows the battery charge and its status.
+pub(super) struct BatterySection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The possible charge collected. If `None`, the section is not shown.
     charge: Option<battery::Charge>,
-    sections_length: &mut prompt::Size,
-) -> Result<()> {
-    let charge = match charge {
-        Some(charge) => charge,
-        None => return Ok(()),
-    };
-    stdout_write!(
-        stdout,
-        "  {} {}{}",
-        match charge.status() {
-            battery::ChargeStatus::Critical =>
-                prompt::color_symbol(if charge.is_charging { "󰢟" } else { "󰂎" }, Color::Red),
-            battery::ChargeStatus::Low =>
-                prompt::color_symbol(if charge.is_charging { "󱊤" } else { "󱊡" }, Color::Red),
-            battery::ChargeStatus::Moderate =>
-                prompt::color_symbol(if charge.is_charging { "󱊥" } else { "󱊢" }, Color::Yellow),
-            battery::ChargeStatus::High =>
-                prompt::color_symbol(if charge.is_charging { "󱊦" } else { "󱊣" }, Color::Green),
-        },
-        charge.percentage,
-        ZSH_PERCENTAGE_SYMBOL
-    )?;
-    *sections_length += format::number_length(charge.percentage)? as prompt::Size + 5;
-    Ok(())
 }
 
-/// Writes the prompt section that shows a calendar with the weekday, month and day of month to the
-/// terminal output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `current_date_time`: the current time to be considered.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_calendar_section(stdout: &mut StdoutLock, current_date_time: DateTime<Local>) -> Result<()> {
-    stdout_write!(
-        stdout,
-        "  {}{}{}",
-        prompt::color_symbol("󰃭 ", Color::Red),
-        current_date_time.format("(%a) %b %d"),
-        current_date_time.day_ordinal(),
-    )
+impl BatterySection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            charge: None,
+        }
+    }
+
+    /// Gets the symbol and color that represent a charge status.
+    ///
+    /// # Parameters
+    /// - `charge`: the charge to be considered.
+    ///
+    /// # Returns
+    /// The symbol and the color.
+    fn symbol_and_color(&self, charge: battery::Charge) -> (&str, Color) {
+        let color = match charge.status() {
+            battery::ChargeStatus::Critical | battery::ChargeStatus::Low => Color::Red,
+            battery::ChargeStatus::Moderate => Color::Yellow,
+            battery::ChargeStatus::High => Color::Green,
+        };
+        let symbol = self.symbols.battery(charge.status(), charge.is_charging);
+        (self.settings.symbol(symbol), self.settings.color(color))
+    }
 }
 
-/// Writes the prompt section that shows a 24-hours clock with the hours and minutes to the terminal
-/// output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `current_date_time`: the current time to be considered.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_clock_section(stdout: &mut StdoutLock, current_date_time: DateTime<Local>) -> Result<()> {
-    stdout_write!(
-        stdout,
-        "  {}{}",
-        match current_date_time.day_fraction() {
-            DayFraction::Dawn => prompt::color_symbol("󰭎 ", Color::Cyan),
-            DayFraction::Morning => prompt::color_symbol("󰖨 ", Color::Red),
-            DayFraction::Afternoon => prompt::color_symbol(" ", Color::Blue),
-            DayFraction::Night => prompt::color_symbol("󰽥 ", Color::Yellow),
-        },
-        current_date_time.format("%Hh%Mm")
-    )
+impl Section for BatterySection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        self.charge = context.battery_charge()?;
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.charge.is_some()
+    }
+
+    fn width(&self) -> prompt::Size {
+        self.charge.map_or(0, |charge| {
+            prompt::width(self.symbol_and_color(charge).0)
+                + format::number_length(charge.percentage).unwrap_or_default() as prompt::Size
+                + 2
+        })
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let charge = match self.charge {
+            Some(charge) => charge,
+            None => return Ok(String::new()),
+        };
+        let (symbol, color) = self.symbol_and_color(charge);
+        Ok(format!(
+            "{} {}{}",
+            shell.color_symbol(symbol, color),
+            charge.percentage,
+            shell.escape("%")
+        ))
+    }
+}
+
+/// Represents the prompt section that shows a calendar with the weekday, month and day of month.
+pub(super) struct CalendarSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The date collected, already formatted.
+    date: Option<String>,
+}
+
+impl CalendarSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            date: None,
+        }
+    }
+}
+
+impl Section for CalendarSection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        self.date = Some(format!(
+            "{}{}",
+            context.current_date_time.format("(%a) %b %d"),
+            context.current_date_time.day_ordinal()
+        ));
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.date.is_some()
+    }
+
+    fn width(&self) -> prompt::Size {
+        self.date.as_ref().map_or(0, |date| {
+            prompt::width(self.settings.symbol(self.symbols.calendar)) + prompt::width(date)
+        })
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let date = match &self.date {
+            Some(date) => date,
+            None => return Ok(String::new()),
+        };
+        Ok(format!(
+            "{}{}",
+            shell.color_symbol(
+                self.settings.symbol(self.symbols.calendar),
+                self.settings.color(Color::Red)
+            ),
+            date
+        ))
+    }
+}
+
+/// Represents the prompt section that shows a 24-hours clock with the hours and minutes.
+pub(super) struct ClockSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The date time collected.
+    current_date_time: Option<DateTime<Local>>,
+}
+
+impl ClockSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            current_date_time: None,
+        }
+    }
+
+    /// Gets the symbol and color that represent the fraction of the day of a date time.
+    ///
+    /// # Parameters
+    /// - `current_date_time`: the date time to be considered.
+    ///
+    /// # Returns
+    /// The symbol and the color.
+    fn symbol_and_color(&self, current_date_time: DateTime<Local>) -> (&str, Color) {
+        let day_fraction = current_date_time.day_fraction();
+        let color = match day_fraction {
+            DayFraction::Dawn => Color::Cyan,
+            DayFraction::Morning => Color::Red,
+            DayFraction::Afternoon => Color::Blue,
+            DayFraction::Night => Color::Yellow,
+        };
+        let symbol = self.symbols.clock(day_fraction);
+        (self.settings.symbol(symbol), self.settings.color(color))
+    }
+}
+
+impl Section for ClockSection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        self.current_date_time = Some(context.current_date_time);
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.current_date_time.is_some()
+    }
+
+    fn width(&self) -> prompt::Size {
+        self.current_date_time.map_or(0, |current_date_time| {
+            prompt::width(self.symbol_and_color(current_date_time).0)
+                + prompt::width(current_date_time.format("%Hh%Mm").to_string())
+        })
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let current_date_time = match self.current_date_time {
+            Some(current_date_time) => current_date_time,
+            None => return Ok(String::new()),
+        };
+        let (symbol, color) = self.symbol_and_color(current_date_time);
+        Ok(format!(
+            "{}{}",
+            shell.color_symbol(symbol, color),
+            current_date_time.format("%Hh%Mm")
+        ))
+    }
 }
 
 /// Writes the prompt separator used to fill the space between the second and third section lines
@@ -224,8 +419,10 @@ fn write_clock_section(stdout: &mut StdoutLock, current_date_time: DateTime<Loca
 ///
 /// # Parameters
 /// - `stdout`: the mutex lock of the stream.
+/// - `shell`: the shell whose syntax is used.
+/// - `symbols`: the symbols to be used.
 /// - `terminal_width`: the terminal width to be considered.
-/// - `sections_length`: a reference to the current prompt length.
+/// - `sections_length`: the length of the upper line, including its decorators.
 ///
 /// # Returns
 /// A possible error.
@@ -237,222 +434,984 @@ fn write_clock_section(stdout: &mut StdoutLock, current_date_time: DateTime<Loca
 /// It panics with a "memory allocation failed" message if any string allocation fails.
 fn write_middle_separator(
     stdout: &mut StdoutLock,
+    shell: &Shell,
+    symbols: &Symbols,
     terminal_width: prompt::Size,
     sections_length: prompt::Size,
 ) -> Result<()> {
-    stdout_write!(stdout, "{}", prompt::color_symbol(")»:", Color::Yellow))?;
+    stdout_write!(
+        stdout,
+        "{}",
+        shell.color_symbol(symbols.upper_line_decorators[1], Color::Yellow)
+    )?;
     for column in 0..terminal_width.saturating_sub(sections_length) {
         stdout_write!(
             stdout,
             "{}",
             if column % 2 == 0 {
-                prompt::color_symbol("-", Color::Red)
+                shell.color_symbol(symbols.middle_separator[0], Color::Red)
             } else {
-                prompt::color_symbol("=", Color::Yellow)
+                shell.color_symbol(symbols.middle_separator[1], Color::Yellow)
             }
         )?;
     }
-    Ok(())
+    stdout_write!(stdout, "{}", shell.line_break())
 }
 
-/// Writes the prompt section that shows a decorator when user is `root` to the terminal output
-/// stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_user_permissions_section(stdout: &mut StdoutLock) -> Result<()> {
-    stdout_write!(
-        stdout,
-        "{}",
-        prompt::show_symbol_when_root(format!(
+/// Represents the prompt section that shows a decorator when user is `root`.
+pub(super) struct UserPermissionsSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+}
+
+impl UserPermissionsSection {
+    /// Creates the section.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig) -> Self {
+        Self { settings }
+    }
+}
+
+impl Section for UserPermissionsSection {
+    fn collect(&mut self, _context: &Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn width(&self) -> prompt::Size {
+        0
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        Ok(shell.show_symbol_when_root(format!(
             "{}{}{}",
-            prompt::color_symbol("{", Color::Yellow),
-            prompt::color_symbol("#", Color::Red),
-            prompt::color_symbol("}", Color::Yellow)
-        ))
-    )
+            shell.color_symbol("{", Color::Yellow),
+            shell.color_symbol(self.settings.symbol("#"), self.settings.color(Color::Red)),
+            shell.color_symbol("}", Color::Yellow)
+        )))
+    }
 }
 
-/// Writes the prompt section that shows different decorators for success and error exit codes to
-/// the terminal output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_exit_code_section(stdout: &mut StdoutLock) -> Result<()> {
-    stdout_write!(
-        stdout,
-        "{}{}{}",
-        prompt::color_symbol("{", Color::Yellow),
-        prompt::show_symbols_for_exit_codes(
-            prompt::color_symbol(ZSH_EXIT_CODE, Color::Yellow),
-            prompt::color_symbol(ZSH_EXIT_CODE, Color::Red)
-        ),
-        prompt::color_symbol("}⤐ ", Color::Yellow)
-    )
+/// Represents the prompt section that shows how long the last command took to run, if it reaches
+/// the configured threshold. Its symbol is placed before the duration and its color, if any, is
+/// applied to both.
+pub(super) struct CommandDurationSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The possible duration collected. If `None`, the section is not shown.
+    duration: Option<Duration>,
 }
 
-/// Writes the prompt section that shows the active Python virtual environment name to the terminal
-/// output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_virtual_env_section(stdout: &mut StdoutLock) -> Result<()> {
-    let virtual_env = match env::var("VIRTUAL_ENV") {
-        Ok(variable) => variable,
-        Err(_) => return Ok(()),
-    };
-    stdout_write!(
-        stdout,
-        " ({})",
-        match Path::new(&virtual_env).file_name() {
-            Some(file_name) => file_name.to_string_lossy(),
-            None => return Ok(()),
+impl CommandDurationSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            duration: None,
         }
-    )
+    }
 }
 
-/// Writes the prompt section that shows the current directory path to the terminal output stream.
-/// It gets abbreviated inside of Git repositories and uses the `~` (for the home directory) and `@`
-/// (for Git repository directories) prefixes.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `current_directory`: the current directory path to be considered.
-/// - `repository`: the possible Git repository to be considered.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_path_section(
-    stdout: &mut StdoutLock,
-    current_directory: impl AsRef<Path>,
-    repository: Option<&git::Repository>,
-) -> Result<()> {
-    stdout_write!(stdout, " ")?;
-    let repository = match repository {
-        Some(repository) if !repository.path.is_root() => repository,
-        _ => return stdout_write!(stdout, "{}", prompt::color_symbol("%~", Color::Red)),
-    };
-    stdout_write!(
-        stdout,
-        "{}",
-        prompt::color_symbol(
-            format!(
-                "@/{}",
-                current_directory
-                    .as_ref()
-                    .strip_prefix(repository.path.parent().ok_or_else(|| anyhow!(
-                        r#"bad use of malformed repository path "{}"."#,
-                        repository.path.display()
-                    ))?)
-                    .map_err(|_| anyhow!(
-                        r#"can not strip prefix of repository path "{}"."#,
-                        repository.path.display()
-                    ))?
-                    .display()
+impl Section for CommandDurationSection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        self.duration = context
+            .command_duration
+            .filter(|duration| *duration >= self.settings.threshold());
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.duration.is_some()
+    }
+
+    fn width(&self) -> prompt::Size {
+        self.duration.map_or(0, |duration| {
+            2 + prompt::width(self.settings.symbol(self.symbols.command_duration))
+                + prompt::width(format::duration(duration))
+        })
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let duration = match self.duration {
+            Some(duration) => duration,
+            None => return Ok(String::new()),
+        };
+        Ok(format!(
+            "{}{}{}",
+            shell.color_symbol("{", Color::Yellow),
+            shell.color_symbol(
+                format!(
+                    "{}{}",
+                    self.settings.symbol(self.symbols.command_duration),
+                    format::duration(duration)
+                ),
+                self.settings.color(Color::Magenta)
             ),
-            Color::Red
-        )
-    )
+            shell.color_symbol("}", Color::Yellow)
+        ))
+    }
 }
 
-/// Writes the prompt section that shows the active branch name and a decorator when it is dirty to
-/// the terminal output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `repository`: the Git repository to be considered.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_git_section(stdout: &mut StdoutLock, repository: Option<&git::Repository>) -> Result<()> {
-    let repository = match repository {
-        Some(repository) => repository,
-        None => return Ok(()),
-    };
-    stdout_write!(stdout, "{}", prompt::color_symbol(":«(", Color::Yellow))?;
-    if let git::Reference::RebaseHash(_) = repository.reference {
-        stdout_write!(
-            stdout,
-            "{}:",
-            prompt::color_symbol("@rebase", Color::Magenta)
-        )?;
+/// Represents the prompt section that shows different decorators for success and error exit codes.
+pub(super) struct ExitCodeSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+}
+
+impl ExitCodeSection {
+    /// Creates the section.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self { settings, symbols }
     }
-    stdout_write!(
-        stdout,
-        "{}{}",
-        match &repository.reference {
-            git::Reference::Branch(branch) => branch,
-            git::Reference::RebaseHash(hash) => hash,
-        },
-        prompt::color_symbol(")»", Color::Yellow)
-    )?;
-    if repository.is_dirty {
-        stdout_write!(stdout, " {}", prompt::color_symbol("✗", Color::Cyan))?;
+}
+
+impl Section for ExitCodeSection {
+    fn collect(&mut self, _context: &Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn width(&self) -> prompt::Size {
+        2 + prompt::width(self.settings.symbol(self.symbols.exit_code))
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        Ok(format!(
+            "{}{}{}{}",
+            shell.color_symbol("{", Color::Yellow),
+            shell.show_symbols_for_exit_codes(
+                shell.color_symbol(shell.exit_code(), Color::Yellow),
+                shell.color_symbol(shell.exit_code(), Color::Red)
+            ),
+            shell.color_symbol("}", Color::Yellow),
+            shell.color_symbol(
+                self.settings.symbol(self.symbols.exit_code),
+                self.settings.color(Color::Yellow)
+            )
+        ))
+    }
+}
+
+/// Represents the prompt section that shows the active Python virtual environment name. Its symbol
+/// is placed before the name and its color, if any, is applied to both.
+pub(super) struct VirtualEnvSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The possible name of the virtual environment collected. If `None`, the section is not shown.
+    name: Option<String>,
+}
+
+impl VirtualEnvSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig) -> Self {
+        Self {
+            settings,
+            name: None,
+        }
+    }
+
+    /// Allocates a string on the heap containing the virtual environment name decorated.
+    ///
+    /// # Parameters
+    /// - `name`: the name of the virtual environment.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    fn decorate(&self, name: &str) -> String {
+        format!("{}({})", self.settings.symbol(""), name)
     }
-    Ok(())
 }
 
-/// Writes the prompt section that shows a decorator when the user does not owns the current
-/// repository, that is, it does not have write permissions, to the terminal output stream.
+impl Section for VirtualEnvSection {
+    fn collect(&mut self, _context: &Context) -> Result<()> {
+        self.name = env::var("VIRTUAL_ENV").ok().and_then(|virtual_env| {
+            Path::new(&virtual_env)
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().into_owned())
+        });
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.name.is_some()
+    }
+
+    fn width(&self) -> prompt::Size {
+        self.name
+            .as_ref()
+            .map_or(0, |name| 1 + prompt::width(self.decorate(name)))
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let name = match &self.name {
+            Some(name) => name,
+            None => return Ok(String::new()),
+        };
+        let name = shell.escape(self.decorate(name));
+        Ok(match self.settings.color {
+            Some(color) => format!(" {}", shell.color_symbol(name, color)),
+            None => format!(" {name}"),
+        })
+    }
+}
+
+/// Represents the prompt section that shows the current directory path. It gets abbreviated inside
+/// of repositories and projects and uses the `~` (for the home directory) and `@` (for repository
+/// directories, or the outermost superproject directories of Git submodules, and for project
+/// directories, marked by entries such as `Cargo.toml`) prefixes. Inside of repositories, their
+/// roots are used unless the projects nested inside of them are preferred in its customizations.
+/// Its symbol replaces the `@` prefix. It can be truncated as set in its customizations, always
+/// preserving the name of the root.
+pub(super) struct PathSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The current directory path collected.
+    current_directory: Option<PathBuf>,
+    /// The possible path of the repository or project root collected.
+    root_path: Option<PathBuf>,
+}
+
+impl PathSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            current_directory: None,
+            root_path: None,
+        }
+    }
+
+    /// Splits the path to be shown into its prefix, which is never truncated, and the names of the
+    /// directories that follow it. The prefix is the `@` prefix followed by the name of the
+    /// repository or project root, the `~` prefix or, for other absolute paths, empty.
+    ///
+    /// # Returns
+    /// The prefix and the names, `None` if the current directory path with the home directory
+    /// abbreviated should be shown as is instead, or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if the path can not be abbreviated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if any allocation fails.
+    fn parts(&self) -> Result<Option<(String, Vec<String>)>> {
+        let current_directory = match &self.current_directory {
+            Some(current_directory) => current_directory,
+            None => return Ok(None),
+        };
+        let names = |path: &Path| {
+            path.components()
+                .filter_map(|component| match component {
+                    Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        match &self.root_path {
+            Some(root_path) if !root_path.is_root() => {
+                let root_name = root_path.file_name().ok_or_else(|| {
+                    anyhow!(
+                        r#"bad use of malformed root path "{}"."#,
+                        root_path.display()
+                    )
+                })?;
+                let relative_path = current_directory.strip_prefix(root_path).map_err(|_| {
+                    anyhow!(
+                        r#"can not strip prefix of root path "{}"."#,
+                        root_path.display()
+                    )
+                })?;
+                Ok(Some((
+                    format!(
+                        "{}/{}",
+                        self.settings.symbol("@"),
+                        root_name.to_string_lossy()
+                    ),
+                    names(relative_path),
+                )))
+            }
+            _ if self.settings.truncation.is_some() => {
+                let home = env::var_os("HOME").filter(|home| !home.is_empty());
+                Ok(Some(
+                    match home.and_then(|home| current_directory.strip_prefix(home).ok()) {
+                        Some(relative_path) => (String::from("~"), names(relative_path)),
+                        None => (String::new(), names(current_directory)),
+                    },
+                ))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Allocates a string on the heap containing the path to be shown, truncated as set in the
+    /// customizations of the section.
+    ///
+    /// # Returns
+    /// The string allocated, `None` if the current directory path with the home directory
+    /// abbreviated should be shown as is instead, or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if the path can not be abbreviated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if any allocation fails.
+    fn path(&self) -> Result<Option<String>> {
+        let (prefix, mut names) = match self.parts()? {
+            Some(parts) => parts,
+            None => return Ok(None),
+        };
+        let ellipsis = self.symbols.placeholder;
+        match self.settings.truncation {
+            Some(Truncation::Fish {}) => {
+                let total_parents = names.len().saturating_sub(1);
+                for name in &mut names[..total_parents] {
+                    let length = if name.starts_with('.') { 2 } else { 1 };
+                    *name = name.chars().take(length).collect();
+                }
+            }
+            Some(Truncation::LastComponents { count }) if names.len() > count => {
+                names.drain(..names.len() - count);
+                names.insert(0, ellipsis.to_string());
+            }
+            _ => {}
+        }
+        let mut path = format!(
+            "{prefix}{}",
+            names
+                .iter()
+                .map(|name| format!("/{name}"))
+                .collect::<String>()
+        );
+        if path.is_empty() {
+            path.push('/');
+        }
+        if let Some(Truncation::MaxWidth { width }) = self.settings.truncation
+            && path.chars().count() > width
+        {
+            let prefix_length = prefix.chars().count();
+            let names_length = path.chars().count() - prefix_length;
+            let available_length = width.saturating_sub(prefix_length + ellipsis.chars().count());
+            let head_length = available_length / 2;
+            let tail_length = available_length - head_length;
+            path = format!(
+                "{prefix}{}{ellipsis}{}",
+                path.chars()
+                    .skip(prefix_length)
+                    .take(head_length)
+                    .collect::<String>(),
+                path.chars()
+                    .skip(prefix_length + names_length - tail_length)
+                    .collect::<String>()
+            );
+        }
+        Ok(Some(path))
+    }
+}
+
+impl Section for PathSection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        let current_directory = directory::current()?;
+        let repository_path = match context.vcs_root() {
+            Some(root) if root.kind != vcs::Kind::Git => Some(root.path.clone()),
+            _ => context.git_repository().map(|repository| {
+                repository
+                    .superproject_path
+                    .clone()
+                    .unwrap_or_else(|| repository.path.clone())
+            }),
+        };
+        let project_path = directory::find_nearest_containing(
+            &current_directory,
+            &self.settings.project_markers(),
+        );
+        self.root_path = choose_root_path(
+            repository_path,
+            project_path,
+            self.settings.prefers_nested_projects(),
+        );
+        self.current_directory = Some(current_directory);
+        Ok(())
+    }
+
+    fn width(&self) -> prompt::Size {
+        match self.path() {
+            Ok(Some(path)) => 1 + prompt::width(path),
+            _ => 1,
+        }
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        Ok(format!(
+            " {}",
+            shell.color_symbol(
+                match (self.path()?, shell.current_directory()) {
+                    (Some(path), _) => shell.escape(path),
+                    (None, Some(current_directory)) => String::from(current_directory),
+                    (None, None) => shell.escape(
+                        self.current_directory
+                            .as_ref()
+                            .map(|current_directory| current_directory.abbreviate_home())
+                            .unwrap_or_default()
+                    ),
+                },
+                self.settings.color(Color::Red)
+            )
+        ))
+    }
+}
+
+/// Chooses the root directory from which the path is abbreviated. Projects are used outside of
+/// repositories, and can only replace the repository root when nested inside of it.
 ///
 /// # Parameters
-/// - `stdout`: the mutex lock of the stream.
+/// - `repository_path`: the possible root directory of the repository, or of the outermost
+///   superproject for Git submodules.
+/// - `project_path`: the possible root directory of the nearest project.
+/// - `prefers_nested_projects`: a boolean that states projects nested inside of the repository are
+///   preferred over its root.
 ///
 /// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_directory_ownership_section(stdout: &mut StdoutLock) -> Result<()> {
-    if !directory::owns_current() {
-        stdout_write!(stdout, " {}", prompt::color_symbol("", Color::Cyan))?;
+/// The possible root directory.
+fn choose_root_path(
+    repository_path: Option<PathBuf>,
+    project_path: Option<PathBuf>,
+    prefers_nested_projects: bool,
+) -> Option<PathBuf> {
+    match (repository_path, project_path) {
+        (Some(repository_path), Some(project_path))
+            if prefers_nested_projects && project_path.starts_with(&repository_path) =>
+        {
+            Some(project_path)
+        }
+        (Some(repository_path), _) => Some(repository_path),
+        (None, project_path) => project_path,
+    }
+}
+
+/// Represents the prompt section that shows the operation in progress with its steps, the active
+/// branch name or, when the HEAD is detached, the exact tag, the description relative to the
+/// nearest tag or the hash checked out, the service hosting its origin remote with its owner and
+/// name, the name of the linked worktree, the age of the commit checked out, colored by how stale
+/// it is, the commits ahead of and behind its upstream, or a decorator when it has none, the number
+/// of stashes, and the number of staged, modified, untracked, renamed and conflicted entries, and
+/// of dirty submodules, unless hidden in its customizations, and the number of lines inserted and
+/// deleted, or a decorator while they are still being computed in the background. For repositories
+/// of other version control systems, it shows their active reference, the ID of the revision
+/// checked out when the reference is a name, and decorators when they are dirty or conflicted. For
+/// Jujutsu, the reference is the bookmarks of the working-copy commit or of its parents, or its
+/// change ID. Its symbol replaces the decorator that opens it and its color applies to the branch
+/// name.
+pub(super) struct GitSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The possible Git repository collected.
+    repository: Option<git::Repository>,
+    /// The possible summary of a repository of another version control system collected, used when
+    /// it is the innermost repository. If `None` and no Git repository has been collected, the
+    /// section is not shown.
+    summary: Option<vcs::Summary>,
+    /// The possible age of the commit checked out collected.
+    commit_age: Option<Duration>,
+}
+
+impl GitSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            repository: None,
+            summary: None,
+            commit_age: None,
+        }
+    }
+
+    /// Gets the reference to be shown, with the symbol and color that identify its type.
+    ///
+    /// # Returns
+    /// The possible symbol, name and color, or `None` if no repository has been collected. If the
+    /// color is `None`, the name is shown uncolored, which is the case of branches unless the color
+    /// of the section has been overridden.
+    fn reference(&self) -> Option<(&'static str, &str, Option<Color>)> {
+        let repository = match (&self.repository, &self.summary) {
+            (Some(repository), _) => repository,
+            (None, Some(summary)) => {
+                return Some((
+                    match summary.kind {
+                        vcs::Kind::Git => "",
+                        vcs::Kind::Jujutsu => self.symbols.jujutsu,
+                        vcs::Kind::Mercurial => self.symbols.mercurial,
+                    },
+                    &summary.reference,
+                    Some(Color::Magenta),
+                ));
+            }
+            (None, None) => return None,
+        };
+        Some(match &repository.reference {
+            git::Reference::Branch(branch) => ("", branch, self.settings.color),
+            git::Reference::Detached(git::Detached::Tag(tag)) => {
+                (self.symbols.git_detached[0], tag, Some(Color::Green))
+            }
+            git::Reference::Detached(git::Detached::Describe(description)) => {
+                (self.symbols.git_detached[1], description, Some(Color::Cyan))
+            }
+            git::Reference::Detached(git::Detached::Hash(hash)) => {
+                (self.symbols.git_detached[2], hash, Some(Color::Blue))
+            }
+        })
+    }
+
+    /// Gets the description of the Git operation in progress to be shown before the reference name.
+    ///
+    /// # Returns
+    /// The possible description, including its progress when available.
+    fn operation_description(&self) -> Option<String> {
+        let repository = self.repository.as_ref()?;
+        repository
+            .operation
+            .map(|operation| match repository.steps {
+                Some(steps) => format!("@{} {}/{}", operation.name(), steps.current, steps.total),
+                None => format!("@{}", operation.name()),
+            })
+    }
+
+    /// Gets the markers to be shown after the reference name.
+    ///
+    /// # Returns
+    /// The markers and their colors.
+    fn markers(&self) -> Vec<(String, Color)> {
+        let mut markers = Vec::new();
+        let repository = match (&self.repository, &self.summary) {
+            (Some(repository), _) => repository,
+            (None, Some(summary)) => {
+                if let Some(revision) = &summary.revision {
+                    markers.push((revision.clone(), Color::Blue));
+                }
+                if summary.is_dirty == Some(true) {
+                    markers.push((self.symbols.git_changes[1].to_string(), Color::Yellow));
+                }
+                if summary.is_conflicted {
+                    markers.push((self.symbols.git_changes[4].to_string(), Color::Red));
+                }
+                return markers;
+            }
+            (None, None) => return markers,
+        };
+        if let Some(remote) = &repository.remote {
+            markers.push((
+                format!(
+                    "{}{}",
+                    self.symbols.git_forges[remote.forge.index()],
+                    remote.path
+                ),
+                Color::Blue,
+            ));
+        }
+        if let Some(worktree) = &repository.worktree {
+            markers.push((
+                format!("{}{}", self.symbols.git_worktree, worktree),
+                Color::Magenta,
+            ));
+        }
+        if let Some(commit_age) = self.commit_age {
+            let [aging, stale] = self.settings.age_thresholds();
+            markers.push((
+                format!("{}{}", self.symbols.git_age, format::age(commit_age)),
+                if commit_age >= stale {
+                    Color::Red
+                } else if commit_age >= aging {
+                    Color::Yellow
+                } else {
+                    Color::Green
+                },
+            ));
+        }
+        match repository.upstream {
+            Some(git::Upstream::Tracking { ahead, behind }) => {
+                if ahead > 0 {
+                    markers.push((
+                        format!("{}{}", self.symbols.git_ahead_behind[0], ahead),
+                        Color::Green,
+                    ));
+                }
+                if behind > 0 {
+                    markers.push((
+                        format!("{}{}", self.symbols.git_ahead_behind[1], behind),
+                        Color::Red,
+                    ));
+                }
+            }
+            Some(git::Upstream::Missing) => {
+                markers.push((self.symbols.git_no_upstream.to_string(), Color::Yellow));
+            }
+            None => {}
+        }
+        if repository.stashes > 0 {
+            markers.push((
+                format!("{}{}", self.symbols.git_stashes, repository.stashes),
+                Color::Cyan,
+            ));
+        }
+        match repository.work_tree_state {
+            git::WorkTreeState::Clean => {}
+            git::WorkTreeState::Dirty(changes) => {
+                let mut counts = changes.counts();
+                if !self.settings.shows_dirty_submodules() {
+                    counts[5] = 0;
+                }
+                for ((count, symbol), color) in
+                    counts.into_iter().zip(self.symbols.git_changes).zip([
+                        Color::Green,
+                        Color::Yellow,
+                        Color::Blue,
+                        Color::Magenta,
+                        Color::Red,
+                        Color::Cyan,
+                    ])
+                {
+                    if count > 0 {
+                        markers.push((format!("{symbol}{count}"), color));
+                    }
+                }
+                if let Some(lines) = changes.lines {
+                    for ((count, symbol), color) in [lines.insertions, lines.deletions]
+                        .into_iter()
+                        .zip(self.symbols.git_lines)
+                        .zip([Color::Green, Color::Red])
+                    {
+                        if count > 0 {
+                            markers.push((format!("{symbol}{count}"), color));
+                        }
+                    }
+                }
+            }
+            git::WorkTreeState::Pending => {
+                markers.push((self.symbols.git_pending.to_string(), Color::Yellow));
+            }
+            git::WorkTreeState::Unknown => {
+                markers.push((self.symbols.git_unknown.to_string(), Color::Magenta));
+            }
+        }
+        markers
+    }
+}
+
+impl Section for GitSection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        if let Some(root) = context
+            .vcs_root()
+            .filter(|root| root.kind != vcs::Kind::Git)
+        {
+            self.summary = vcs::backend(root.kind).summarize(&root.path);
+            return Ok(());
+        }
+        self.repository = context.git_repository().cloned();
+        self.commit_age = self
+            .repository
+            .as_ref()
+            .and_then(|repository| repository.commit_time)
+            .map(|commit_time| commit_time.elapsed_until(&context.current_date_time));
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.repository.is_some() || self.summary.is_some()
+    }
+
+    fn width(&self) -> prompt::Size {
+        let (symbol, name, _) = match self.reference() {
+            Some(reference) => reference,
+            None => return 0,
+        };
+        let mut width = prompt::width(self.settings.symbol(self.symbols.git_decorators[0]))
+            + prompt::width(self.symbols.git_decorators[1])
+            + prompt::width(symbol)
+            + prompt::width(name);
+        if let Some(description) = self.operation_description() {
+            width += prompt::width(&description) + 1;
+        }
+        for (marker, _) in self.markers() {
+            width += 1 + prompt::width(&marker);
+        }
+        width
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let (symbol, name, color) = match self.reference() {
+            Some(reference) => reference,
+            None => return Ok(String::new()),
+        };
+        let mut section = shell.color_symbol(
+            self.settings.symbol(self.symbols.git_decorators[0]),
+            Color::Yellow,
+        );
+        if let Some(description) = self.operation_description() {
+            section.push_str(&format!(
+                "{}:",
+                shell.color_symbol(description, Color::Magenta)
+            ));
+        }
+        let reference = format!("{symbol}{}", shell.escape(name));
+        section.push_str(&format!(
+            "{}{}",
+            match color {
+                Some(color) => shell.color_symbol(reference, color),
+                None => reference,
+            },
+            shell.color_symbol(self.symbols.git_decorators[1], Color::Yellow)
+        ));
+        for (marker, color) in self.markers() {
+            section.push_str(&format!(
+                " {}",
+                shell.color_symbol(shell.escape(marker), color)
+            ));
+        }
+        Ok(section)
     }
-    Ok(())
 }
 
-/// Writes the left prompt to the terminal output stream.
+/// Represents the prompt section that shows the identity used to create commits in Git repositories:
+/// the initials of the author name and the domain of their email, followed by a decorator when
+/// commits are signed. It is colored as a warning when the email does not match the one expected
+/// for the current directory tree.
+pub(super) struct GitIdentitySection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The possible identity collected. If `None`, the section is not shown.
+    identity: Option<git::Identity>,
+    /// A boolean collected that states the email is the one expected for the current directory.
+    is_expected: bool,
+}
+
+impl GitIdentitySection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            identity: None,
+            is_expected: true,
+        }
+    }
+
+    /// Gets the badge that compactly identifies the author, without its decorators.
+    ///
+    /// # Returns
+    /// The possible badge or `None` if no identity with a name or email has been collected.
+    fn badge(&self) -> Option<String> {
+        let identity = self.identity.as_ref()?;
+        let initials = identity.name.as_deref().map(|name| {
+            name.split_whitespace()
+                .filter_map(|word| word.chars().next())
+                .flat_map(char::to_uppercase)
+                .collect::<String>()
+        });
+        let domain = identity
+            .email
+            .as_deref()
+            .map(|email| email.rsplit_once('@').map_or(email, |(_, domain)| domain));
+        match (initials, domain) {
+            (Some(initials), Some(domain)) => Some(format!("{initials}@{domain}")),
+            (Some(initials), None) => Some(initials),
+            (None, Some(domain)) => Some(format!("@{domain}")),
+            (None, None) => None,
+        }
+    }
+
+    /// Checks whether commits are signed.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    fn is_signed(&self) -> bool {
+        self.identity
+            .as_ref()
+            .is_some_and(|identity| identity.signs_commits)
+    }
+}
+
+impl Section for GitIdentitySection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        if context
+            .vcs_root()
+            .is_none_or(|root| root.kind != vcs::Kind::Git)
+        {
+            return Ok(());
+        }
+        self.identity = git::find_identity();
+        if let Some(identity) = &self.identity {
+            self.is_expected = directory::current().map_or(true, |current_directory| {
+                self.settings
+                    .is_expected_email(&current_directory, identity.email.as_deref())
+            });
+        }
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.badge().is_some()
+    }
+
+    fn width(&self) -> prompt::Size {
+        let badge = match self.badge() {
+            Some(badge) => badge,
+            None => return 0,
+        };
+        let mut width = 1
+            + prompt::width(self.settings.symbol(self.symbols.git_identity))
+            + prompt::width(badge);
+        if self.is_signed() {
+            width += prompt::width(self.symbols.git_signed);
+        }
+        width
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let badge = match self.badge() {
+            Some(badge) => badge,
+            None => return Ok(String::new()),
+        };
+        let mut section = format!(
+            " {}",
+            shell.color_symbol(
+                format!(
+                    "{}{}",
+                    self.settings.symbol(self.symbols.git_identity),
+                    shell.escape(badge)
+                ),
+                if self.is_expected {
+                    self.settings.color(Color::Cyan)
+                } else {
+                    Color::Red
+                }
+            )
+        );
+        if self.is_signed() {
+            section.push_str(&shell.color_symbol(self.symbols.git_signed, Color::Green));
+        }
+        Ok(section)
+    }
+}
+
+/// Represents the prompt section that shows a decorator when the user does not owns the current
+/// repository, that is, it does not have write permissions.
+pub(super) struct DirectoryOwnershipSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// A boolean collected that states the user owns the current directory.
+    owns_current: Option<bool>,
+}
+
+impl DirectoryOwnershipSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            owns_current: None,
+        }
+    }
+}
+
+impl Section for DirectoryOwnershipSection {
+    fn collect(&mut self, _context: &Context) -> Result<()> {
+        self.owns_current = Some(directory::owns_current());
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.owns_current == Some(false)
+    }
+
+    fn width(&self) -> prompt::Size {
+        1 + prompt::width(self.settings.symbol(self.symbols.directory_ownership))
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        Ok(format!(
+            " {}",
+            shell.color_symbol(
+                self.settings.symbol(self.symbols.directory_ownership),
+                self.settings.color(Color::Cyan)
+            )
+        ))
+    }
+}
+
+/// Writes the left prompt to the terminal output stream. Its sections are written in the order
+/// defined by the user configuration, skipping the disabled ones.
+///
+/// # Parameters
+/// - `config`: the user configuration to be considered.
+/// - `context`: the data shared between the sections of the prompt.
 ///
 /// # Returns
 /// A possible error.
@@ -461,38 +1420,188 @@ fn write_directory_ownership_section(stdout: &mut StdoutLock) -> Result<()> {
 /// It returns an empty error if it fails to:
 /// - Write to the stream.
 /// - Retrieve the terminal window dimensions.
-/// - Retrieve the battery charge metadata.
-/// - Retrieve the current directory path.
+/// - Create the threads that collect the data of the sections.
 ///
 /// # Panics
 /// It panics with a "memory allocation failed" message if any string allocation fails.
-pub(crate) fn write() -> Result<()> {
-    let terminal_width = terminal::size()
-        .map(|(width, _)| width)
-        .map_err(|_| anyhow!("can not retrieve the terminal dimensions."))?;
-    let disk_usage = disk::usage()?;
-    let battery_charge = battery::charge()?;
-    let current_date_time = Local::now();
-    let git_repository = git::find_repository();
-    let current_directory = directory::current()?;
-    let mut sections_length = SECTIONS_CONSTANT_LENGTH;
+pub(crate) fn write(config: &Config, context: Context) -> Result<()> {
+    let terminal_width = prompt::terminal_width()?;
+    let symbols = config.symbol_set.symbols();
+    let context = Arc::new(context);
+    let mut upper_sections = Registry::new(config, Line::LeftUpper);
+    let mut lower_sections = Registry::new(config, Line::LeftLower);
+    section::collect(&mut [&mut upper_sections, &mut lower_sections], &context)?;
     let mut stdout = io::stdout().lock();
-    write_top_separator(&mut stdout, terminal_width)?;
-    write_local_ip_section(
+    write_top_separator(&mut stdout, &context.shell, symbols, terminal_width)?;
+    stdout_write!(&mut stdout, "{}", upper_sections.render(&context.shell)?)?;
+    write_middle_separator(
         &mut stdout,
-        local_ip_address::local_ip().ok(),
-        &mut sections_length,
+        &context.shell,
+        symbols,
+        terminal_width,
+        symbols
+            .upper_line_decorators
+            .iter()
+            .map(prompt::width)
+            .sum::<prompt::Size>()
+            + upper_sections.width(),
     )?;
-    write_disk_section(&mut stdout, disk_usage, &mut sections_length)?;
-    write_battery_section(&mut stdout, battery_charge, &mut sections_length)?;
-    write_calendar_section(&mut stdout, current_date_time)?;
-    write_clock_section(&mut stdout, current_date_time)?;
-    write_middle_separator(&mut stdout, terminal_width, sections_length)?;
-    write_user_permissions_section(&mut stdout)?;
-    write_exit_code_section(&mut stdout)?;
-    write_virtual_env_section(&mut stdout)?;
-    write_path_section(&mut stdout, current_directory, git_repository.as_ref())?;
-    write_git_section(&mut stdout, git_repository.as_ref())?;
-    write_directory_ownership_section(&mut stdout)?;
-    stdout_write!(&mut stdout, " \n")
+    stdout_write!(&mut stdout, "{} \n", lower_sections.render(&context.shell)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt::symbols::SymbolSet;
+
+    #[test]
+    fn repository_roots_are_preferred_by_default() {
+        let superproject = PathBuf::from("/work/superproject");
+        let submodule = superproject.join("vendor/library");
+        assert_eq!(
+            choose_root_path(Some(superproject.clone()), Some(submodule), false),
+            Some(superproject)
+        );
+    }
+
+    #[test]
+    fn nested_projects_are_preferred_when_enabled() {
+        let superproject = PathBuf::from("/work/superproject");
+        let submodule = superproject.join("vendor/library");
+        assert_eq!(
+            choose_root_path(Some(superproject), Some(submodule.clone()), true),
+            Some(submodule)
+        );
+    }
+
+    #[test]
+    fn projects_are_used_outside_of_repositories() {
+        let workspace = PathBuf::from("/work");
+        let repository = workspace.join("repository");
+        assert_eq!(
+            choose_root_path(Some(repository.clone()), Some(workspace.clone()), true),
+            Some(repository)
+        );
+        assert_eq!(
+            choose_root_path(None, Some(workspace.clone()), false),
+            Some(workspace)
+        );
+        assert_eq!(choose_root_path(None, None, true), None);
+    }
+
+    /// Creates a path section truncated as given with the paths it collects.
+    fn path_section(
+        truncation: Truncation,
+        current_directory: &Path,
+        root_path: Option<&Path>,
+    ) -> PathSection {
+        let settings = SectionConfig {
+            truncation: Some(truncation),
+            ..SectionConfig::default()
+        };
+        let mut section = PathSection::new(settings, SymbolSet::Unicode.symbols());
+        section.current_directory = Some(current_directory.to_path_buf());
+        section.root_path = root_path.map(Path::to_path_buf);
+        section
+    }
+
+    /// Gets the path shown by a section truncated as given.
+    fn truncated_path(
+        truncation: Truncation,
+        current_directory: &Path,
+        root_path: Option<&Path>,
+    ) -> String {
+        path_section(truncation, current_directory, root_path)
+            .path()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn paths_within_the_maximum_width_are_not_truncated() {
+        let directory = Path::new("/usr/share/doc");
+        for width in [14, 20] {
+            let truncation = Truncation::MaxWidth { width };
+            assert_eq!(
+                truncated_path(truncation, directory, None),
+                "/usr/share/doc"
+            );
+        }
+        let truncation = Truncation::LastComponents { count: 3 };
+        assert_eq!(
+            truncated_path(truncation, directory, None),
+            "/usr/share/doc"
+        );
+    }
+
+    #[test]
+    fn paths_beyond_the_maximum_width_are_truncated_in_the_middle() {
+        let truncation = Truncation::MaxWidth { width: 13 };
+        assert_eq!(
+            truncated_path(truncation, Path::new("/usr/share/doc"), None),
+            "/usr/s…re/doc"
+        );
+        let truncation = Truncation::LastComponents { count: 2 };
+        assert_eq!(
+            truncated_path(truncation, Path::new("/usr/share/doc"), None),
+            "/…/share/doc"
+        );
+        let truncation = Truncation::Fish {};
+        assert_eq!(
+            truncated_path(truncation, Path::new("/usr/.local/share"), None),
+            "/u/.l/share"
+        );
+    }
+
+    #[test]
+    fn home_directory_is_abbreviated_before_truncating() {
+        let home = PathBuf::from(env::var_os("HOME").expect("HOME must be set to run the tests."));
+        let directory = home.join("projects/river/src");
+        let truncation = Truncation::MaxWidth { width: 12 };
+        assert_eq!(truncated_path(truncation, &directory, None), "~/proj…r/src");
+        let truncation = Truncation::LastComponents { count: 2 };
+        assert_eq!(
+            truncated_path(truncation, &directory, None),
+            "~/…/river/src"
+        );
+        let truncation = Truncation::MaxWidth { width: 1 };
+        assert_eq!(truncated_path(truncation, &home, None), "~");
+    }
+
+    #[test]
+    fn paths_relative_to_roots_keep_the_root_name() {
+        let root = Path::new("/work/project");
+        let directory = root.join("src/module/deep");
+        let truncation = Truncation::MaxWidth { width: 15 };
+        assert_eq!(
+            truncated_path(truncation, &directory, Some(root)),
+            "@/project/s…eep"
+        );
+        let truncation = Truncation::MaxWidth { width: 25 };
+        assert_eq!(
+            truncated_path(truncation, &directory, Some(root)),
+            "@/project/src/module/deep"
+        );
+        let truncation = Truncation::LastComponents { count: 1 };
+        assert_eq!(
+            truncated_path(truncation, &directory, Some(root)),
+            "@/project/…/deep"
+        );
+        let truncation = Truncation::Fish {};
+        assert_eq!(
+            truncated_path(truncation, &directory, Some(root)),
+            "@/project/s/m/deep"
+        );
+    }
+
+    #[test]
+    fn filesystem_root_is_shown_as_is() {
+        let truncation = Truncation::MaxWidth { width: 1 };
+        assert_eq!(truncated_path(truncation, Path::new("/"), None), "/");
+        let truncation = Truncation::LastComponents { count: 1 };
+        assert_eq!(
+            truncated_path(truncation, Path::new("/"), Some(Path::new("/"))),
+            "/"
+        );
+    }
 }
@@ -1,28 +1,34 @@
 //! Provides features related to prompt rendering.
 
 use std::io;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local};
+use crossterm::terminal;
+use serde::Deserialize;
 
 use crate::command_line::stdout_write;
+use crate::hardware::{battery, disk};
+use crate::prompt::shell::Shell;
+use crate::file_system::directory;
+use crate::{daemon, git, vcs};
 
 pub(crate) mod left;
 pub(crate) mod right;
+pub(crate) mod section;
+pub(crate) mod shell;
+pub(crate) mod symbols;
 
 /// Represents the terminal size unit.
-type Size = u16;
-
-/// The symbol used in ZSH to escape the percentage sign.
-const ZSH_PERCENTAGE_SYMBOL: &str = "%%";
-/// The symbol used in ZSH that gets replaced by the exit code of the last
-/// command.
-const ZSH_EXIT_CODE: &str = "%?";
-/// The symbol used in ZSH that gets replaced by the total of background jobs.
-const ZSH_JOBS_COUNT: &str = "%j";
-
-/// Contains a subset of the available ANSI colors that can be used in ZSH.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Color {
+pub(crate) type Size = u16;
+
+/// Contains a subset of the available ANSI colors that can be used in the prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Color {
     /// Refers to the dark red color (ANSI 1).
     Red,
     /// Refers to the dark green color (ANSI 2).
@@ -54,91 +60,217 @@ impl Color {
     }
 }
 
-/// Writes the ZSH script that initiates the prompt to the terminal output stream.
-///
-/// This output should be redirected and appended to the `~/.zshrc`.
-///
-/// # Returns
-/// An error on failure.
-///
-/// # Errors
-/// It returns an error if it fails to write to the stream.
-pub(crate) fn init() -> Result<()> {
-    let mut stdout = io::stdout().lock();
-    stdout_write!(
-        &mut stdout,
-        "setopt promptsubst;
-export VIRTUAL_ENV_DISABLE_PROMPT=1;
-PROMPT='$(river_dreams prompt left)';
-RPROMPT='$(river_dreams prompt right)';"
-    )
+/// Represents the data shared between the sections of a prompt, which collect their data
+/// concurrently.
+pub(crate) struct Context {
+    /// The shell the prompt is being written for.
+    pub(crate) shell: Shell,
+    /// The date time the prompt is being written at.
+    pub(crate) current_date_time: DateTime<Local>,
+    /// The possible duration of the last command. If `None`, no command has run since the last
+    /// prompt.
+    pub(crate) command_duration: Option<Duration>,
+    /// The possible state of the work tree of the Git repository computed in the background.
+    git_work_tree_state: Option<git::WorkTreeState>,
+    /// The maximum number of changed entries for which the lines changed in the Git repository are
+    /// counted.
+    git_diff_limit: usize,
+    /// The possible Git repository of the current directory, found on its first use.
+    git_repository: OnceLock<Option<git::Repository>>,
+    /// The possible root of the innermost repository of any version control system containing the
+    /// current directory, found on its first use.
+    vcs_root: OnceLock<Option<vcs::Root>>,
 }
 
-/// Allocates a string on the heap that wraps a symbol using the specified foreground color using
-/// the ZSH syntax.
-///
-/// # Parameters
-/// - `symbol`: the symbol to be wrapped.
-/// - `color`: the foreground color to be used.
+impl Context {
+    /// Parses the context of a prompt being written now from the data provided through
+    /// command-line options.
+    ///
+    /// # Parameters
+    /// - `shell`: the shell the prompt is being written for.
+    /// - `git_status`: the possible name of the state of the work tree of the Git repository
+    ///   computed in the background. If `None`, it is computed when the repository is found.
+    /// - `command_duration`: the possible duration of the last command in milliseconds. If `None`
+    ///   or empty, no command has run since the last prompt.
+    /// - `git_diff_limit`: the maximum number of changed entries for which the lines changed in the
+    ///   Git repository are counted. If zero, they are never counted.
+    ///
+    /// # Returns
+    /// The context or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if any value provided is not valid.
+    pub(crate) fn parse(
+        shell: Shell,
+        git_status: Option<&str>,
+        command_duration: Option<&str>,
+        git_diff_limit: usize,
+    ) -> Result<Self> {
+        Ok(Self {
+            shell,
+            current_date_time: Local::now(),
+            command_duration: match command_duration.filter(|duration| !duration.is_empty()) {
+                Some(duration) => {
+                    Some(Duration::from_millis(duration.parse().map_err(|_| {
+                        anyhow!(r#"invalid command duration "{duration}" provided."#)
+                    })?))
+                }
+                None => None,
+            },
+            git_work_tree_state: match git_status {
+                Some(argument) => Some(
+                    git::WorkTreeState::from_argument(argument)
+                        .ok_or_else(|| anyhow!(r#"invalid Git status "{argument}" provided."#))?,
+                ),
+                None => None,
+            },
+            git_diff_limit,
+            git_repository: OnceLock::new(),
+            vcs_root: OnceLock::new(),
+        })
+    }
+
+    /// Gets the Git repository of the current directory, finding it on the first call. Concurrent
+    /// calls wait for the first one to finish.
+    ///
+    /// # Returns
+    /// The possible repository found.
+    pub(crate) fn git_repository(&self) -> Option<&git::Repository> {
+        self.git_repository
+            .get_or_init(|| git::find_repository(self.git_work_tree_state, self.git_diff_limit))
+            .as_ref()
+    }
+
+    /// Gets the root of the innermost repository of any version control system containing the
+    /// current directory, finding it on the first call. Concurrent calls wait for the first one to
+    /// finish.
+    ///
+    /// # Returns
+    /// The possible root found.
+    pub(crate) fn vcs_root(&self) -> Option<&vcs::Root> {
+        self.vcs_root
+            .get_or_init(|| {
+                directory::current()
+                    .ok()
+                    .and_then(|current_directory| vcs::find_root(&current_directory))
+            })
+            .as_ref()
+    }
+
+    /// Gets the local IP address, taking it from the cache of the daemon when available.
+    ///
+    /// # Returns
+    /// The possible address found.
+    pub(crate) fn local_ip(&self) -> Option<IpAddr> {
+        daemon::cache()
+            .and_then(|cache| cache.local_ip)
+            .or_else(|| local_ip_address::local_ip().ok())
+    }
+
+    /// Gets the disk usage, taking it from the cache of the daemon when available.
+    ///
+    /// # Returns
+    /// The usage or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if the usage can not be retrieved.
+    pub(crate) fn disk_usage(&self) -> Result<disk::Usage> {
+        match daemon::cache().and_then(|cache| cache.disk_usage) {
+            Some(usage) => Ok(usage),
+            None => disk::usage(),
+        }
+    }
+
+    /// Gets the battery charge, taking it from the cache of the daemon when available.
+    ///
+    /// # Returns
+    /// The possible charge, if a battery is available, or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if the charge can not be retrieved.
+    pub(crate) fn battery_charge(&self) -> Result<Option<battery::Charge>> {
+        match daemon::cache().and_then(|cache| cache.battery_charge) {
+            Some(charge) => Ok(charge),
+            None => battery::charge(),
+        }
+    }
+}
+
+/// Gets the terminal width. When serving a request of the daemon, the width of the client terminal
+/// is used instead.
 ///
 /// # Returns
-/// The string allocated.
+/// The width or an error.
 ///
-/// # Panics
-/// It panics with a "memory allocation failed" message if the allocation fails.
-fn color_symbol(symbol: impl AsRef<str>, color: Color) -> String {
-    format!("%F{{{}}}{}%f", color.ansi(), symbol.as_ref())
+/// # Errors
+/// It returns a displayable error if the terminal dimensions can not be retrieved.
+pub(crate) fn terminal_width() -> Result<Size> {
+    if let Some(width) = daemon::terminal_width() {
+        return Ok(width);
+    }
+    terminal::size()
+        .map(|(width, _)| width)
+        .map_err(|_| anyhow!("can not retrieve the terminal dimensions."))
 }
 
-/// Allocates a string on the heap that wraps a symbol to be shown only when the user is `root`
-/// using the ZSH syntax.
+/// Gets the width in columns a text occupies in the terminal, considering that each of its
+/// characters, including Nerd Font symbols, occupies a single column.
 ///
 /// # Parameters
-/// - `symbol`: the symbol to be wrapped.
+/// - `text`: the text to be measured.
 ///
 /// # Returns
-/// The string allocated.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if the allocation fails.
-fn show_symbol_when_root(symbol: impl AsRef<str>) -> String {
-    format!("%(#.{}.)", symbol.as_ref())
+/// The width.
+fn width(text: impl AsRef<str>) -> Size {
+    text.as_ref().chars().count() as Size
 }
 
-/// Allocates a string on the heap that wraps two symbols to be shown for success and error exit
-/// codes using the ZSH syntax.
+/// Writes the script that initiates the prompt in a shell to the terminal output stream.
+///
+/// This output should be evaluated during the shell startup, e.g. by appending it to the
+/// `~/.zshrc` or to the `~/.bashrc`.
 ///
 /// # Parameters
-/// - `on_success_symbol`: the wrapped symbol to be shown on success codes.
-/// - `on_error_symbol`: the wrapped symbol to be show on error codes.
+/// - `shell`: the shell to be initiated.
 ///
 /// # Returns
-/// The string allocated.
+/// An error on failure.
 ///
-/// # Panics
-/// It panics with a "memory allocation failed" message if the allocation fails.
-fn show_symbols_for_exit_codes(
-    on_success_symbol: impl AsRef<str>,
-    on_error_symbol: impl AsRef<str>,
-) -> String {
-    format!(
-        "%(?.{}.{})",
-        on_success_symbol.as_ref(),
-        on_error_symbol.as_ref()
-    )
+/// # Errors
+/// It returns an error if it fails to write to the stream.
+pub(crate) fn init(shell: &Shell) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout_write!(&mut stdout, "{}", shell.init_script())
 }
 
-/// Allocates a string on the heap that wraps a symbol to be shown only when there are background
-/// jobs running using the ZSH syntax.
+/// Writes the state of the work tree of the Git repository of the current directory to the
+/// terminal output stream, followed by a line break. Nothing is written outside of a repository
+/// or when the innermost one found belongs to another version control system, e.g. a Jujutsu
+/// repository colocated with Git, as the prompt does not use that state for it.
+///
+/// It is meant to be run in the background by the shell, which provides its output to the left
+/// prompt when it finishes.
 ///
 /// # Parameters
-/// - `symbol`: the symbol to be wrapped.
+/// - `diff_limit`: the maximum number of changed entries for which the lines changed are counted.
+///   If zero, they are never counted.
 ///
 /// # Returns
-/// The string allocated.
+/// An error on failure.
 ///
-/// # Panics
-/// It panics with a "memory allocation failed" message if the allocation fails.
-fn show_symbol_when_job(symbol: impl AsRef<str>) -> String {
-    format!("%(1j.{}.)", symbol.as_ref())
+/// # Errors
+/// It returns an error if it fails to write to the stream.
+pub(crate) fn write_git_work_tree_state(diff_limit: usize) -> Result<()> {
+    if directory::current()
+        .ok()
+        .and_then(|current_directory| vcs::find_root(&current_directory))
+        .is_none_or(|root| root.kind != vcs::Kind::Git)
+    {
+        return Ok(());
+    }
+    let mut stdout = io::stdout().lock();
+    match git::find_work_tree_state(diff_limit) {
+        Some(state) => stdout_write!(&mut stdout, "{}\n", state.to_argument()),
+        None => Ok(()),
+    }
 }
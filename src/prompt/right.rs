@@ -1,123 +1,224 @@
 //! Provides features related to the right prompt rendering.
 
-use std::io::{self, StdoutLock};
+use std::io;
+use std::sync::Arc;
 
 use anyhow::Result;
 use num_format::{Locale, ToFormattedString as _};
 
 use crate::command_line::stdout_write;
+use crate::config::{Config, Line, SectionConfig};
 use crate::file_system::directory;
-use crate::prompt::{self, Color};
+use crate::prompt::section::{self, Registry, Section};
+use crate::prompt::shell::{JobsCount, Shell};
+use crate::prompt::symbols::Symbols;
+use crate::prompt::{self, Color, Context};
 
-/// Writes a single directory entry type count to the terminal output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `symbol`: the symbol to be used to represent the type.
-/// - `color`: the color the symbol will have.
-/// - `count`: the count of the type.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_entry_type_count(
-    stdout: &mut StdoutLock,
-    symbol: impl AsRef<str>,
-    color: Option<Color>,
-    count: directory::entry::TypeCount,
-) -> Result<()> {
-    if count == 0 {
-        return Ok(());
+/// The colors used to represent each directory entry type, in the same order of their symbols.
+const ENTRY_TYPE_COLORS: [Option<Color>; 9] = [
+    Some(Color::Yellow),
+    None,
+    Some(Color::Cyan),
+    Some(Color::Blue),
+    Some(Color::Magenta),
+    Some(Color::Green),
+    Some(Color::Blue),
+    Some(Color::Red),
+    Some(Color::Magenta),
+];
+
+/// Represents the prompt section that shows the total of each entry type in the current directory.
+/// Its symbol is shown before the totals and its color replaces the ones of every entry type.
+pub(super) struct EntryTypeCountsSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The counts collected.
+    type_counts: Option<directory::entry::TypeCounts>,
+}
+
+impl EntryTypeCountsSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            type_counts: None,
+        }
     }
-    stdout_write!(
-        stdout,
-        " {}{}",
-        if let Some(color) = color {
-            prompt::color_symbol(symbol.as_ref(), color)
+
+    /// Gets the counts of each entry type, in the same order of their symbols.
+    ///
+    /// # Returns
+    /// The counts.
+    fn counts(&self) -> [directory::entry::TypeCount; 9] {
+        let type_counts = self.type_counts.clone().unwrap_or_default();
+        [
+            type_counts.total_directories,
+            type_counts.total_files,
+            type_counts.total_sockets,
+            type_counts.total_fifos,
+            type_counts.total_blocks,
+            type_counts.total_characters,
+            type_counts.total_symlinks,
+            type_counts.total_hiddens,
+            type_counts.total_temporaries,
+        ]
+    }
+}
+
+impl Section for EntryTypeCountsSection {
+    fn collect(&mut self, _context: &Context) -> Result<()> {
+        self.type_counts = Some(directory::current_entry_type_counts());
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.counts().iter().any(|count| *count != 0)
+    }
+
+    fn width(&self) -> prompt::Size {
+        let symbol = self.settings.symbol("");
+        (if symbol.is_empty() {
+            0
         } else {
-            String::from(symbol.as_ref())
-        },
-        count.to_formatted_string(&Locale::en)
-    )
+            1 + prompt::width(symbol)
+        }) + self
+            .symbols
+            .entry_types
+            .iter()
+            .zip(self.counts())
+            .filter(|(_, count)| *count != 0)
+            .map(|(symbol, count)| {
+                1 + prompt::width(symbol) + prompt::width(count.to_formatted_string(&Locale::en))
+            })
+            .sum::<prompt::Size>()
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        let symbol = self.settings.symbol("");
+        let mut section = String::new();
+        if !symbol.is_empty() {
+            section.push_str(&format!(
+                " {}",
+                match self.settings.color {
+                    Some(color) => shell.color_symbol(symbol, color),
+                    None => String::from(symbol),
+                }
+            ));
+        }
+        section.extend(
+            self.symbols
+                .entry_types
+                .iter()
+                .zip(ENTRY_TYPE_COLORS)
+                .zip(self.counts())
+                .filter(|(_, count)| *count != 0)
+                .map(|((symbol, color), count)| {
+                    format!(
+                        " {}{}",
+                        if let Some(color) = self.settings.color.or(color) {
+                            shell.color_symbol(symbol, color)
+                        } else {
+                            String::from(*symbol)
+                        },
+                        count.to_formatted_string(&Locale::en)
+                    )
+                }),
+        );
+        Ok(section)
+    }
 }
 
-/// Writes the prompt section that shows the total of each entry type in the current directory to
-/// the terminal output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the stream.
-/// - `type_counts`: the count to be considered.
-///
-/// # Returns
-/// A possible error.
-///
-/// # Errors
-/// It returns an empty error if it fails to write to the stream.
-///
-/// # Panics
-/// It panics with a "memory allocation failed" message if any string allocation fails.
-fn write_entry_type_counts_section(
-    stdout: &mut StdoutLock,
-    type_counts: &directory::entry::TypeCounts,
-) -> Result<()> {
-    write_entry_type_count(
-        stdout,
-        " ",
-        Some(Color::Yellow),
-        type_counts.total_directories,
-    )?;
-    write_entry_type_count(stdout, " ", None, type_counts.total_files)?;
-    write_entry_type_count(stdout, "󱄙 ", Some(Color::Cyan), type_counts.total_sockets)?;
-    write_entry_type_count(stdout, "󰟦 ", Some(Color::Blue), type_counts.total_fifos)?;
-    write_entry_type_count(stdout, "󰇖 ", Some(Color::Magenta), type_counts.total_blocks)?;
-    write_entry_type_count(
-        stdout,
-        "󱣴 ",
-        Some(Color::Green),
-        type_counts.total_characters,
-    )?;
-    write_entry_type_count(stdout, "󰌷 ", Some(Color::Blue), type_counts.total_symlinks)?;
-    write_entry_type_count(stdout, "󰈉 ", Some(Color::Red), type_counts.total_hiddens)?;
-    write_entry_type_count(
-        stdout,
-        "󱣹 ",
-        Some(Color::Magenta),
-        type_counts.total_temporaries,
-    )
+/// Represents the prompt section that displays the total of jobs running in the background.
+pub(super) struct JobsSection {
+    /// The user customizations of the section.
+    settings: SectionConfig,
+    /// The default symbols of the prompt.
+    symbols: &'static Symbols,
+    /// The possible total of jobs collected. If `None`, the shell expands it by itself.
+    jobs_count: Option<JobsCount>,
 }
 
-/// Writes the prompt section that displays the total of jobs running in the background to the
-/// terminal output stream.
-///
-/// # Parameters
-/// - `stdout`: the mutex lock of the
-fn write_jobs_section(stdout: &mut StdoutLock) -> Result<()> {
-    stdout_write!(
-        stdout,
-        "{}",
-        prompt::show_symbol_when_job(format!(
+impl JobsSection {
+    /// Creates the section without any collected data.
+    ///
+    /// # Parameters
+    /// - `settings`: the user customizations of the section.
+    /// - `symbols`: the default symbols of the prompt.
+    ///
+    /// # Returns
+    /// The section.
+    pub(super) fn new(settings: SectionConfig, symbols: &'static Symbols) -> Self {
+        Self {
+            settings,
+            symbols,
+            jobs_count: None,
+        }
+    }
+}
+
+impl Section for JobsSection {
+    fn collect(&mut self, context: &Context) -> Result<()> {
+        self.jobs_count = context.shell.state().map(|state| state.jobs_count);
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.jobs_count != Some(0)
+    }
+
+    fn width(&self) -> prompt::Size {
+        self.jobs_count.map_or(0, |jobs_count| {
+            2 + prompt::width(self.settings.symbol(self.symbols.jobs))
+                + prompt::width(jobs_count.to_string())
+        })
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        Ok(shell.show_symbol_when_job(format!(
             " {} {}",
-            prompt::color_symbol("", Color::Magenta),
-            prompt::ZSH_JOBS_COUNT,
-        ))
-    )
+            shell.color_symbol(
+                self.settings.symbol(self.symbols.jobs),
+                self.settings.color(Color::Magenta)
+            ),
+            shell.jobs_count(),
+        )))
+    }
 }
 
-/// Writes the right prompt to the terminal output stream.
+/// Writes the right prompt to the terminal output stream. Its sections are written in the order
+/// defined by the user configuration, skipping the disabled ones. If the shell does not place the
+/// right prompt by itself, it is aligned to the right of the line the cursor is at.
+///
+/// # Parameters
+/// - `config`: the user configuration to be considered.
+/// - `context`: the data shared between the sections of the prompt.
 ///
 /// # Returns
 /// A possible error.
 ///
 /// # Errors
-/// It returns an empty error if it fails to write to the stream.
-pub(crate) fn write() -> Result<()> {
+/// It returns an empty error if it fails to write to the stream, to retrieve the terminal window
+/// dimensions or to create the threads that collect the data of the sections.
+pub(crate) fn write(config: &Config, context: Context) -> Result<()> {
+    let context = Arc::new(context);
+    let mut sections = Registry::new(config, Line::Right);
+    section::collect(&mut [&mut sections], &context)?;
+    let mut prompt = sections.render(&context.shell)?;
+    if !context.shell.has_right_prompt() {
+        prompt = context
+            .shell
+            .align_right(prompt, sections.width(), prompt::terminal_width()?);
+    }
     let mut stdout = io::stdout().lock();
-    write_entry_type_counts_section(&mut stdout, &directory::current_entry_type_counts())?;
-    write_jobs_section(&mut stdout)?;
-    stdout_write!(&mut stdout, "\n")
+    stdout_write!(&mut stdout, "{}\n", prompt)
 }
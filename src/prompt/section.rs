@@ -0,0 +1,258 @@
+//! Provides features to collect, measure and render the prompt sections.
+
+use std::mem;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+
+use crate::config::{Config, Line, SectionId};
+use crate::prompt::shell::Shell;
+use crate::prompt::{self, Context, left, right};
+
+/// Represents the result of a section that has collected its data in its own thread, along with
+/// the moment it has finished.
+type Collected = (Box<dyn Section>, Result<()>, Instant);
+
+/// Provides members to collect the data of a prompt section, measure it and render it. Sections
+/// collect their data in their own threads, so they must be sendable between them.
+pub(crate) trait Section: Send {
+    /// Collects the data required to render the section.
+    ///
+    /// # Parameters
+    /// - `context`: the data shared between the sections of the prompt.
+    ///
+    /// # Returns
+    /// A possible error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if the data can not be retrieved.
+    fn collect(&mut self, context: &Context) -> Result<()>;
+    /// Checks whether the section has anything to show. Sections that do not are neither rendered
+    /// nor separated from the others.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    fn is_visible(&self) -> bool {
+        true
+    }
+    /// Gets the width in columns the section occupies when rendered. Content that is only expanded
+    /// by the shell when the prompt is shown is not counted.
+    ///
+    /// # Returns
+    /// The width.
+    fn width(&self) -> prompt::Size;
+    /// Allocates a string on the heap containing the section rendered.
+    ///
+    /// # Parameters
+    /// - `shell`: the shell whose syntax is used.
+    ///
+    /// # Returns
+    /// The string allocated or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if the collected data can not be rendered.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if any string allocation fails.
+    fn render(&self, shell: &Shell) -> Result<String>;
+}
+
+/// Represents the section shown in place of a section that has not collected its data in time.
+struct PlaceholderSection {
+    /// The text to be shown, including its padding.
+    text: String,
+}
+
+impl Section for PlaceholderSection {
+    fn collect(&mut self, _context: &Context) -> Result<()> {
+        Ok(())
+    }
+
+    fn width(&self) -> prompt::Size {
+        prompt::width(&self.text)
+    }
+
+    fn render(&self, shell: &Shell) -> Result<String> {
+        Ok(shell.escape(&self.text))
+    }
+}
+
+/// Represents the sections of a prompt line, in the order they are rendered.
+pub(crate) struct Registry {
+    /// The sections to be rendered, each one along with the time it has to collect its data.
+    sections: Vec<(Box<dyn Section>, Duration)>,
+    /// The separator placed between visible sections.
+    separator: &'static str,
+    /// The text shown in place of sections that have not collected their data in time.
+    placeholder: String,
+}
+
+impl Registry {
+    /// Creates the registry of a prompt line containing its sections enabled by the user
+    /// configuration.
+    ///
+    /// # Parameters
+    /// - `config`: the user configuration to be considered.
+    /// - `line`: the line to be considered.
+    ///
+    /// # Returns
+    /// The registry.
+    pub(crate) fn new(config: &Config, line: Line) -> Self {
+        Self {
+            sections: config
+                .enabled_sections(line)
+                .map(|section| (create(section, config), config.section(section).timeout()))
+                .collect(),
+            separator: match line {
+                Line::LeftUpper => "  ",
+                Line::LeftLower | Line::Right => "",
+            },
+            placeholder: format!(
+                "{}{}",
+                match line {
+                    Line::LeftUpper => "",
+                    Line::LeftLower | Line::Right => " ",
+                },
+                config.symbol_set.symbols().placeholder
+            ),
+        }
+    }
+
+    /// Gets the width in columns the sections and their separators occupy when rendered.
+    ///
+    /// # Returns
+    /// The width.
+    pub(crate) fn width(&self) -> prompt::Size {
+        let mut total_visible: prompt::Size = 0;
+        let mut width = 0;
+        for (section, _) in self
+            .sections
+            .iter()
+            .filter(|(section, _)| section.is_visible())
+        {
+            total_visible += 1;
+            width += section.width();
+        }
+        width + prompt::width(self.separator) * total_visible.saturating_sub(1)
+    }
+
+    /// Allocates a string on the heap containing all visible sections rendered and separated.
+    ///
+    /// # Parameters
+    /// - `shell`: the shell whose syntax is used.
+    ///
+    /// # Returns
+    /// The string allocated or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if any section can not be rendered.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if any string allocation fails.
+    pub(crate) fn render(&self, shell: &Shell) -> Result<String> {
+        let mut rendered_sections = Vec::with_capacity(self.sections.len());
+        for (section, _) in self
+            .sections
+            .iter()
+            .filter(|(section, _)| section.is_visible())
+        {
+            rendered_sections.push(section.render(shell)?);
+        }
+        Ok(rendered_sections.join(self.separator))
+    }
+}
+
+/// Collects the data required to render the sections of some registries concurrently. Each section
+/// collects its data in its own thread and, if it does not finish in the time it has, it is replaced
+/// by a placeholder while its thread is left behind. If it fails, it is also replaced by a
+/// placeholder, so that it does not prevent the rest of the prompt from being shown.
+///
+/// # Parameters
+/// - `registries`: the registries whose sections should collect their data.
+/// - `context`: the data shared between the sections of the prompt.
+///
+/// # Returns
+/// A possible error.
+///
+/// # Errors
+/// It returns a displayable error if a thread can not be created.
+pub(crate) fn collect(registries: &mut [&mut Registry], context: &Arc<Context>) -> Result<()> {
+    let started_at = Instant::now();
+    let mut receivers: Vec<Vec<Receiver<Collected>>> = Vec::with_capacity(registries.len());
+    for registry in registries.iter_mut() {
+        let mut registry_receivers = Vec::with_capacity(registry.sections.len());
+        for (section, _) in &mut registry.sections {
+            let mut section = mem::replace(
+                section,
+                Box::new(PlaceholderSection {
+                    text: registry.placeholder.clone(),
+                }),
+            );
+            let context = Arc::clone(context);
+            let (sender, receiver) = mpsc::channel();
+            thread::Builder::new()
+                .spawn(move || {
+                    let result = section.collect(&context);
+                    _ = sender.send((section, result, Instant::now()));
+                })
+                .map_err(|_| anyhow!("can not create a thread to collect the sections data."))?;
+            registry_receivers.push(receiver);
+        }
+        receivers.push(registry_receivers);
+    }
+    for (registry, registry_receivers) in registries.iter_mut().zip(receivers) {
+        for ((section, timeout), receiver) in registry.sections.iter_mut().zip(registry_receivers) {
+            let deadline = started_at + *timeout;
+            if let Ok((collected_section, result, finished_at)) =
+                receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                && finished_at <= deadline
+                && result.is_ok()
+            {
+                *section = collected_section;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Allocates a section on the heap.
+///
+/// # Parameters
+/// - `section`: the section to be allocated.
+/// - `config`: the user configuration the section customizations are taken from.
+///
+/// # Returns
+/// The section allocated.
+///
+/// # Panics
+/// It panics with a "memory allocation failed" message if the allocation fails.
+fn create(section: SectionId, config: &Config) -> Box<dyn Section> {
+    let settings = config.section(section).clone();
+    let symbols = config.symbol_set.symbols();
+    match section {
+        SectionId::LocalIp => Box::new(left::LocalIpSection::new(settings, symbols)),
+        SectionId::Disk => Box::new(left::DiskSection::new(settings, symbols)),
+        SectionId::Battery => Box::new(left::BatterySection::new(settings, symbols)),
+        SectionId::Calendar => Box::new(left::CalendarSection::new(settings, symbols)),
+        SectionId::Clock => Box::new(left::ClockSection::new(settings, symbols)),
+        SectionId::UserPermissions => Box::new(left::UserPermissionsSection::new(settings)),
+        SectionId::CommandDuration => {
+            Box::new(left::CommandDurationSection::new(settings, symbols))
+        }
+        SectionId::ExitCode => Box::new(left::ExitCodeSection::new(settings, symbols)),
+        SectionId::VirtualEnv => Box::new(left::VirtualEnvSection::new(settings)),
+        SectionId::Path => Box::new(left::PathSection::new(settings, symbols)),
+        SectionId::Git => Box::new(left::GitSection::new(settings, symbols)),
+        SectionId::GitIdentity => Box::new(left::GitIdentitySection::new(settings, symbols)),
+        SectionId::DirectoryOwnership => {
+            Box::new(left::DirectoryOwnershipSection::new(settings, symbols))
+        }
+        SectionId::EntryTypeCounts => {
+            Box::new(right::EntryTypeCountsSection::new(settings, symbols))
+        }
+        SectionId::Jobs => Box::new(right::JobsSection::new(settings, symbols)),
+    }
+}
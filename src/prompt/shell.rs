@@ -0,0 +1,522 @@
+//! Provides features to write the prompt using the syntax of each supported shell.
+
+use anyhow::{Result, anyhow};
+
+use crate::prompt::{Color, Size};
+
+/// Represents the exit code of a command.
+pub(crate) type ExitCode = i32;
+/// Represents a total of background jobs.
+pub(crate) type JobsCount = usize;
+
+/// The ZSH script that initiates the prompt. Before each prompt, its `precmd` hook starts
+/// computing the Git changes in the background, discarding a computation still running, and
+/// writes the prompt with a pending state. When the computation finishes, the `zle -F` widget
+/// watching its file descriptor stores the result and redraws the prompt with it. The duration of
+/// the last command is measured between its `preexec` and `precmd` hooks. Its commands must be
+/// separated by semicolons as its output is meant to be evaluated unquoted.
+const ZSH_INIT_SCRIPT: &str = r#"setopt promptsubst;
+export VIRTUAL_ENV_DISABLE_PROMPT=1;
+zmodload zsh/datetime;
+typeset -g __river_dreams_git_status=pending __river_dreams_git_status_fd=;
+typeset -g __river_dreams_command_start= __river_dreams_command_duration=;
+__river_dreams_discard_git_status() {
+    if [[ -n $__river_dreams_git_status_fd ]]; then
+        zle -F $__river_dreams_git_status_fd;
+        exec {__river_dreams_git_status_fd}<&-;
+        __river_dreams_git_status_fd=;
+    fi;
+};
+__river_dreams_git_status_widget() {
+    local git_status;
+    IFS= read -r -u $__river_dreams_git_status_fd git_status;
+    __river_dreams_discard_git_status;
+    if [[ -n $git_status ]]; then
+        __river_dreams_git_status=$git_status;
+        zle reset-prompt;
+        zle -R;
+    fi;
+};
+__river_dreams_preexec() {
+    __river_dreams_command_start=$EPOCHREALTIME;
+};
+__river_dreams_precmd() {
+    local -i command_duration;
+    __river_dreams_command_duration=;
+    if [[ -n $__river_dreams_command_start ]]; then
+        (( command_duration = (EPOCHREALTIME - __river_dreams_command_start) * 1000 ));
+        __river_dreams_command_duration=$command_duration;
+        __river_dreams_command_start=;
+    fi;
+    __river_dreams_discard_git_status;
+    __river_dreams_git_status=pending;
+    exec {__river_dreams_git_status_fd}< <(river_dreams git-status 2>/dev/null);
+    zle -F -w $__river_dreams_git_status_fd __river_dreams_git_status_widget;
+};
+zle -N __river_dreams_git_status_widget;
+autoload -Uz add-zsh-hook;
+add-zsh-hook preexec __river_dreams_preexec;
+add-zsh-hook precmd __river_dreams_precmd;
+PROMPT='$(river_dreams prompt left --git-status $__river_dreams_git_status --duration "$__river_dreams_command_duration")';
+RPROMPT='$(river_dreams prompt right)';"#;
+/// The Bash script that initiates the prompt. As Bash can not expand the exit code and the total
+/// of jobs inside of the prompt, they are resolved by its `PROMPT_COMMAND` and provided as options.
+/// The start of each command is recorded by an arithmetic expansion in `PS0`, which requires Bash
+/// 5.0 or later to measure its duration. Its commands must be separated by semicolons as its output
+/// is meant to be evaluated unquoted.
+const BASH_INIT_SCRIPT: &str = r#"export VIRTUAL_ENV_DISABLE_PROMPT=1;
+__river_dreams_prompt_command() {
+    local exit_code=$? jobs_count='\j' command_duration=;
+    jobs_count="${jobs_count@P}";
+    if [[ -n $__river_dreams_command_start ]]; then
+        command_duration=$(( (${EPOCHREALTIME/[.,]} - __river_dreams_command_start) / 1000 ));
+        __river_dreams_command_start=;
+    fi;
+    PS1="$(river_dreams prompt left --shell bash --exit-code $exit_code --jobs $jobs_count --duration "$command_duration")$(river_dreams prompt right --shell bash --exit-code $exit_code --jobs $jobs_count)";
+};
+if [[ -n $EPOCHREALTIME ]]; then
+    PS0='${__river_dreams_null[__river_dreams_command_start=${EPOCHREALTIME/[.,]}]}'"$PS0";
+fi;
+PROMPT_COMMAND="__river_dreams_prompt_command${PROMPT_COMMAND:+;$PROMPT_COMMAND}";"#;
+/// The Fish script that initiates the prompt. As Fish can not expand the exit code and the total
+/// of jobs inside of the prompt, they are resolved by its prompt functions and provided as options,
+/// along with the duration of the last command measured by Fish.
+const FISH_INIT_SCRIPT: &str = "set -gx VIRTUAL_ENV_DISABLE_PROMPT 1;
+function fish_prompt;
+    river_dreams prompt left --shell fish --exit-code $status --jobs (count (jobs -g)) --duration \"$CMD_DURATION\";
+end;
+function fish_right_prompt;
+    river_dreams prompt right --shell fish --jobs (count (jobs -g));
+end;";
+/// The Nushell script that initiates the prompt. As Nushell can not expand the exit code and the
+/// total of jobs inside of the prompt, they are resolved by its prompt closures and provided as
+/// options, along with the duration of the last command measured by Nushell. The trailing line
+/// break of the prompt is removed, as Nushell would show it.
+const NU_INIT_SCRIPT: &str = r#"$env.VIRTUAL_ENV_DISABLE_PROMPT = 1
+$env.PROMPT_INDICATOR = ""
+$env.PROMPT_INDICATOR_VI_INSERT = ""
+$env.PROMPT_INDICATOR_VI_NORMAL = ""
+$env.config.render_right_prompt_on_last_line = true
+$env.PROMPT_COMMAND = {||
+    river_dreams prompt left --shell nu --exit-code $env.LAST_EXIT_CODE --jobs (job list | length) --duration ($env.CMD_DURATION_MS? | default "") | str trim --right --char "\n"
+}
+$env.PROMPT_COMMAND_RIGHT = {||
+    river_dreams prompt right --shell nu --jobs (job list | length) | str trim --right --char "\n"
+}"#;
+
+/// Represents the state of the shell resolved before the prompt is written. It is required by
+/// shells that can not expand it inside of the prompt by themselves.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct State {
+    /// The exit code of the last command.
+    pub(crate) exit_code: ExitCode,
+    /// The total of jobs running in the background.
+    pub(crate) jobs_count: JobsCount,
+    /// A boolean that states the user is `root`.
+    pub(crate) is_root: bool,
+}
+
+impl State {
+    /// Parses the state provided through command-line options. Whether the user is `root` is
+    /// resolved from the effective user ID of the process, which is inherited from the shell.
+    ///
+    /// # Parameters
+    /// - `exit_code`: the possible exit code provided. If `None`, `0` is used.
+    /// - `jobs_count`: the possible total of jobs provided. If `None`, `0` is used.
+    ///
+    /// # Returns
+    /// The state or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if any value provided is not a valid number.
+    pub(crate) fn parse(exit_code: Option<&str>, jobs_count: Option<&str>) -> Result<Self> {
+        Ok(Self {
+            exit_code: match exit_code {
+                Some(exit_code) => exit_code
+                    .parse()
+                    .map_err(|_| anyhow!(r#"invalid exit code "{exit_code}" provided."#))?,
+                None => 0,
+            },
+            jobs_count: match jobs_count {
+                Some(jobs_count) => jobs_count
+                    .parse()
+                    .map_err(|_| anyhow!(r#"invalid jobs count "{jobs_count}" provided."#))?,
+                None => 0,
+            },
+            is_root: unsafe { libc::geteuid() } == 0,
+        })
+    }
+}
+
+/// Contains the shells the prompt can be written for.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Shell {
+    /// ZSH, which expands the exit code, the total of jobs and the user permissions inside of the
+    /// prompt by itself.
+    Zsh,
+    /// Bash, whose state is resolved by its `PROMPT_COMMAND`.
+    Bash(State),
+    /// Fish, whose state is resolved by its prompt functions. Its prompt is written verbatim, using
+    /// raw ANSI escape sequences.
+    Fish(State),
+    /// Nushell, whose state is resolved by its prompt closures. Its prompt is written verbatim,
+    /// using raw ANSI escape sequences.
+    Nu(State),
+}
+
+impl Shell {
+    /// Creates a shell from its name.
+    ///
+    /// # Parameters
+    /// - `name`: the name of the shell, as used in the command-line.
+    /// - `state`: the state of the shell, used only by shells that can not expand it by
+    ///   themselves.
+    ///
+    /// # Returns
+    /// The shell or an error.
+    ///
+    /// # Errors
+    /// It returns a displayable error if the shell is not supported.
+    pub(crate) fn new(name: &str, state: State) -> Result<Self> {
+        match name {
+            "zsh" => Ok(Self::Zsh),
+            "bash" => Ok(Self::Bash(state)),
+            "fish" => Ok(Self::Fish(state)),
+            "nu" => Ok(Self::Nu(state)),
+            _ => Err(anyhow!(r#"invalid shell "{name}" provided."#)),
+        }
+    }
+
+    /// Gets the name of the shell, as used in the command-line.
+    ///
+    /// # Returns
+    /// The name.
+    pub(crate) const fn name(&self) -> &'static str {
+        match self {
+            Self::Zsh => "zsh",
+            Self::Bash(_) => "bash",
+            Self::Fish(_) => "fish",
+            Self::Nu(_) => "nu",
+        }
+    }
+
+    /// Gets the state of the shell resolved before the prompt is written.
+    ///
+    /// # Returns
+    /// The state or `None` if the shell expands it by itself.
+    pub(crate) const fn state(&self) -> Option<&State> {
+        match self {
+            Self::Zsh => None,
+            Self::Bash(state) | Self::Fish(state) | Self::Nu(state) => Some(state),
+        }
+    }
+
+    /// Gets the script that initiates the prompt in the shell.
+    ///
+    /// # Returns
+    /// The script.
+    pub(crate) const fn init_script(&self) -> &'static str {
+        match self {
+            Self::Zsh => ZSH_INIT_SCRIPT,
+            Self::Bash(_) => BASH_INIT_SCRIPT,
+            Self::Fish(_) => FISH_INIT_SCRIPT,
+            Self::Nu(_) => NU_INIT_SCRIPT,
+        }
+    }
+
+    /// Checks whether the shell places the right prompt by itself. If it does not, the right prompt
+    /// must be aligned using the `align_right` method.
+    ///
+    /// # Returns
+    /// A boolean that states that.
+    pub(crate) const fn has_right_prompt(&self) -> bool {
+        matches!(self, Self::Zsh | Self::Fish(_) | Self::Nu(_))
+    }
+
+    /// Gets the line break placed after lines that fill the whole terminal width. Shells that
+    /// measure each line of the prompt by themselves require an explicit one, while the others rely
+    /// on the terminal wrapping the line.
+    ///
+    /// # Returns
+    /// The line break.
+    pub(crate) const fn line_break(&self) -> &'static str {
+        match self {
+            Self::Zsh | Self::Bash(_) => "",
+            Self::Fish(_) | Self::Nu(_) => "\n",
+        }
+    }
+
+    /// Allocates a string on the heap containing a text escaped, so that the shell shows it
+    /// verbatim.
+    ///
+    /// # Parameters
+    /// - `text`: the text to be escaped.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    pub(crate) fn escape(&self, text: impl AsRef<str>) -> String {
+        let mut escaped_text = String::with_capacity(text.as_ref().len());
+        for character in text.as_ref().chars() {
+            match (self, character) {
+                (Self::Zsh, '%') => escaped_text.push_str("%%"),
+                (Self::Bash(_), '\\') => escaped_text.push_str(r"\\\\"),
+                (Self::Bash(_), '$' | '`') => {
+                    escaped_text.push_str(r"\\");
+                    escaped_text.push(character);
+                }
+                // An octal escape is used as, in POSIX mode, Bash replaces `!` by the history number
+                // and `!!` by `!`, while it shows both verbatim otherwise.
+                (Self::Bash(_), '!') => escaped_text.push_str(r"\041"),
+                _ => escaped_text.push(character),
+            }
+        }
+        escaped_text
+    }
+
+    /// Gets the symbol that gets replaced by the current directory path, with the home directory
+    /// abbreviated as `~`.
+    ///
+    /// # Returns
+    /// The symbol or `None` if the shell can not expand it by itself.
+    pub(crate) const fn current_directory(&self) -> Option<&'static str> {
+        match self {
+            Self::Zsh => Some("%~"),
+            Self::Bash(_) => Some(r"\w"),
+            Self::Fish(_) | Self::Nu(_) => None,
+        }
+    }
+
+    /// Allocates a string on the heap containing the exit code of the last command or a symbol
+    /// that gets replaced by it.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    pub(crate) fn exit_code(&self) -> String {
+        match self.state() {
+            None => String::from("%?"),
+            Some(state) => state.exit_code.to_string(),
+        }
+    }
+
+    /// Allocates a string on the heap containing the total of background jobs or a symbol that gets
+    /// replaced by it.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    pub(crate) fn jobs_count(&self) -> String {
+        match self.state() {
+            None => String::from("%j"),
+            Some(state) => state.jobs_count.to_string(),
+        }
+    }
+
+    /// Allocates a string on the heap that wraps a symbol using the specified foreground color.
+    ///
+    /// # Parameters
+    /// - `symbol`: the symbol to be wrapped.
+    /// - `color`: the foreground color to be used.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    pub(crate) fn color_symbol(&self, symbol: impl AsRef<str>, color: Color) -> String {
+        match self {
+            Self::Zsh => format!("%F{{{}}}{}%f", color.ansi(), symbol.as_ref()),
+            Self::Bash(_) => format!(r"\[\e[3{}m\]{}\[\e[39m\]", color.ansi(), symbol.as_ref()),
+            Self::Fish(_) | Self::Nu(_) => {
+                format!("\x1b[3{}m{}\x1b[39m", color.ansi(), symbol.as_ref())
+            }
+        }
+    }
+
+    /// Allocates a string on the heap that wraps a symbol to be shown only when the user is `root`.
+    ///
+    /// # Parameters
+    /// - `symbol`: the symbol to be wrapped.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    pub(crate) fn show_symbol_when_root(&self, symbol: impl AsRef<str>) -> String {
+        match self.state() {
+            None => format!("%(#.{}.)", symbol.as_ref()),
+            Some(state) if state.is_root => String::from(symbol.as_ref()),
+            Some(_) => String::new(),
+        }
+    }
+
+    /// Allocates a string on the heap that wraps two symbols to be shown for success and error exit
+    /// codes.
+    ///
+    /// # Parameters
+    /// - `on_success_symbol`: the wrapped symbol to be shown on success codes.
+    /// - `on_error_symbol`: the wrapped symbol to be show on error codes.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    pub(crate) fn show_symbols_for_exit_codes(
+        &self,
+        on_success_symbol: impl AsRef<str>,
+        on_error_symbol: impl AsRef<str>,
+    ) -> String {
+        match self.state() {
+            None => format!(
+                "%(?.{}.{})",
+                on_success_symbol.as_ref(),
+                on_error_symbol.as_ref()
+            ),
+            Some(state) if state.exit_code == 0 => String::from(on_success_symbol.as_ref()),
+            Some(_) => String::from(on_error_symbol.as_ref()),
+        }
+    }
+
+    /// Allocates a string on the heap that wraps a symbol to be shown only when there are
+    /// background jobs running.
+    ///
+    /// # Parameters
+    /// - `symbol`: the symbol to be wrapped.
+    ///
+    /// # Returns
+    /// The string allocated.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    pub(crate) fn show_symbol_when_job(&self, symbol: impl AsRef<str>) -> String {
+        match self.state() {
+            None => format!("%(1j.{}.)", symbol.as_ref()),
+            Some(state) if state.jobs_count > 0 => String::from(symbol.as_ref()),
+            Some(_) => String::new(),
+        }
+    }
+
+    /// Allocates a string on the heap that places a rendered prompt at the right of the line the
+    /// cursor is at, without moving the cursor. It is used by shells that do not place the right
+    /// prompt by themselves, leaving a column between the prompt and the terminal border like ZSH
+    /// does.
+    ///
+    /// # Parameters
+    /// - `prompt`: the rendered prompt to be placed.
+    /// - `width`: the width in columns the prompt occupies.
+    /// - `terminal_width`: the terminal width to be considered.
+    ///
+    /// # Returns
+    /// The string allocated. It is empty if the prompt does not fit in the line.
+    ///
+    /// # Panics
+    /// It panics with a "memory allocation failed" message if the allocation fails.
+    pub(crate) fn align_right(
+        &self,
+        prompt: impl AsRef<str>,
+        width: Size,
+        terminal_width: Size,
+    ) -> String {
+        match self {
+            Self::Zsh | Self::Fish(_) | Self::Nu(_) => String::from(prompt.as_ref()),
+            Self::Bash(_) if width == 0 || width >= terminal_width => String::new(),
+            Self::Bash(_) => format!(
+                r"\[\e7\e[{}G{}\e8\]",
+                terminal_width - width,
+                strip_bash_markers(prompt.as_ref())
+            ),
+        }
+    }
+}
+
+/// Allocates a string on the heap containing a Bash prompt without the `\[` and `\]` markers that
+/// delimit its non-printing sequences, so that it can be placed inside of another one. As every
+/// backslash written by the prompt starts a two characters sequence, escaped backslashes are never
+/// mistaken by markers.
+///
+/// # Parameters
+/// - `prompt`: the prompt to be considered.
+///
+/// # Returns
+/// The string allocated.
+///
+/// # Panics
+/// It panics with a "memory allocation failed" message if the allocation fails.
+fn strip_bash_markers(prompt: &str) -> String {
+    let mut stripped_prompt = String::with_capacity(prompt.len());
+    let mut characters = prompt.chars();
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            stripped_prompt.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('[' | ']') => {}
+            Some(escaped_character) => {
+                stripped_prompt.push(character);
+                stripped_prompt.push(escaped_character);
+            }
+            None => stripped_prompt.push(character),
+        }
+    }
+    stripped_prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text escaped by the tests, containing every character special to any of the shells.
+    const SPECIAL_TEXT: &str = r"100% \ $HOME `id` !!";
+
+    #[test]
+    fn zsh_escapes_percent_signs() {
+        assert_eq!(Shell::Zsh.escape(SPECIAL_TEXT), r"100%% \ $HOME `id` !!");
+    }
+
+    #[test]
+    fn bash_escapes_expansions_and_history() {
+        assert_eq!(
+            Shell::Bash(State::default()).escape(SPECIAL_TEXT),
+            r"100% \\\\ \\$HOME \\`id\\` \041\041"
+        );
+    }
+
+    #[test]
+    fn fish_and_nu_write_text_verbatim() {
+        assert_eq!(
+            Shell::Fish(State::default()).escape(SPECIAL_TEXT),
+            SPECIAL_TEXT
+        );
+        assert_eq!(
+            Shell::Nu(State::default()).escape(SPECIAL_TEXT),
+            SPECIAL_TEXT
+        );
+    }
+
+    #[test]
+    fn bash_markers_are_stripped() {
+        assert_eq!(
+            strip_bash_markers(r"\[\e[31m\]red\[\e[0m\]"),
+            r"\e[31mred\e[0m"
+        );
+        assert_eq!(strip_bash_markers(r"trailing\"), r"trailing\");
+    }
+
+    #[test]
+    fn escaped_text_is_kept_when_stripping_bash_markers() {
+        let escaped_text = Shell::Bash(State::default()).escape(SPECIAL_TEXT);
+        assert_eq!(strip_bash_markers(&escaped_text), escaped_text);
+        let escaped_text = Shell::Bash(State::default()).escape(r"\[not a marker\]");
+        assert_eq!(strip_bash_markers(&escaped_text), escaped_text);
+    }
+}
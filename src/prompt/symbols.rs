@@ -0,0 +1,283 @@
+//! Provides the sets of symbols the prompt can be written with.
+
+use serde::Deserialize;
+
+use crate::datetime::DayFraction;
+use crate::hardware::{battery, disk};
+
+/// The symbols that require a font patched by the Nerd Fonts project.
+static NERD_FONT_SYMBOLS: Symbols = Symbols {
+    top_separator: ["≥", "v"],
+    middle_separator: ["-", "="],
+    upper_line_decorators: [":«(", ")»:"],
+    local_ip: " ",
+    disk: ["󰋊 ", "󰋊 ", "󰋊 "],
+    battery: [["󰂎", "󰢟"], ["󱊡", "󱊤"], ["󱊢", "󱊥"], ["󱊣", "󱊦"]],
+    calendar: "󰃭 ",
+    clock: ["󰭎 ", "󰖨 ", " ", "󰽥 "],
+    command_duration: "󱎫 ",
+    exit_code: "⤐ ",
+    git_decorators: [":«(", ")»"],
+    git_worktree: " ",
+    git_age: " ",
+    git_detached: [" ", " ", " "],
+    git_changes: ["󰐕", "󰏫", "󰋖", "󰑕", "󰀦", ""],
+    git_lines: ["+", "-"],
+    git_forges: [" ", " ", " ", " ", " ", " "],
+    mercurial: "☿ ",
+    jujutsu: "jj:",
+    git_pending: "󰔟",
+    git_unknown: "",
+    git_identity: " ",
+    git_signed: "",
+    git_ahead_behind: ["⇡", "⇣"],
+    git_no_upstream: "",
+    git_stashes: "",
+    directory_ownership: "",
+    entry_types: [" ", " ", "󱄙 ", "󰟦 ", "󰇖 ", "󱣴 ", "󰌷 ", "󰈉 ", "󱣹 "],
+    jobs: "",
+    placeholder: "…",
+};
+/// The symbols available in most fonts with good Unicode coverage.
+static UNICODE_SYMBOLS: Symbols = Symbols {
+    top_separator: ["≥", "v"],
+    middle_separator: ["-", "="],
+    upper_line_decorators: [":«(", ")»:"],
+    local_ip: "⇄ ",
+    disk: ["○ ", "◑ ", "● "],
+    battery: [["▁", "▁↯"], ["▃", "▃↯"], ["▅", "▅↯"], ["▇", "▇↯"]],
+    calendar: "▦ ",
+    clock: ["◒ ", "☼ ", "◓ ", "☾ "],
+    command_duration: "⧗ ",
+    exit_code: "⤐ ",
+    git_decorators: [":«(", ")»"],
+    git_worktree: "⑂ ",
+    git_age: "◷ ",
+    git_detached: ["⚑ ", "⚐ ", "⌗ "],
+    git_changes: ["●", "✎", "?", "→", "≠", "⊞"],
+    git_lines: ["+", "-"],
+    git_forges: ["gh:", "gl:", "bb:", "cb:", "gt:", "⌂ "],
+    mercurial: "☿ ",
+    jujutsu: "jj:",
+    git_pending: "…",
+    git_unknown: "⍰",
+    git_identity: "☺ ",
+    git_signed: "⚿",
+    git_ahead_behind: ["⇡", "⇣"],
+    git_no_upstream: "∅",
+    git_stashes: "≡",
+    directory_ownership: "⊘",
+    entry_types: ["◆ ", "◇ ", "◎ ", "‖ ", "▣ ", "▤ ", "↪ ", "◌ ", "≈ "],
+    jobs: "⚙",
+    placeholder: "…",
+};
+/// The symbols that only use ASCII characters, readable in any terminal.
+static ASCII_SYMBOLS: Symbols = Symbols {
+    top_separator: [">", "v"],
+    middle_separator: ["-", "="],
+    upper_line_decorators: [":<(", ")>:"],
+    local_ip: "ip:",
+    disk: ["hd:", "hd:", "hd!"],
+    battery: [
+        ["[.  ]", "[.  ]+"],
+        ["[|  ]", "[|  ]+"],
+        ["[|| ]", "[|| ]+"],
+        ["[|||]", "[|||]+"],
+    ],
+    calendar: "cal:",
+    clock: ["_ ", "^ ", "- ", "* "],
+    command_duration: "took:",
+    exit_code: "> ",
+    git_decorators: [":<(", ")>"],
+    git_worktree: "wt:",
+    git_age: "age:",
+    git_detached: ["tag:", "~", "#"],
+    git_changes: ["S", "!", "?", ">", "=", "sub"],
+    git_lines: ["+", "-"],
+    git_forges: ["gh:", "gl:", "bb:", "cb:", "gt:", "git:"],
+    mercurial: "hg:",
+    jujutsu: "jj:",
+    git_pending: "?",
+    git_unknown: "n/a",
+    git_identity: "id:",
+    git_signed: "+sig",
+    git_ahead_behind: ["^", "v"],
+    git_no_upstream: "-",
+    git_stashes: "$",
+    directory_ownership: "[ro]",
+    entry_types: ["d:", "f:", "s:", "p:", "b:", "c:", "l:", "h:", "t:"],
+    jobs: "&",
+    placeholder: "...",
+};
+
+/// Contains the sets of symbols the prompt can be written with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SymbolSet {
+    /// The symbols that require a font patched by the Nerd Fonts project.
+    #[default]
+    NerdFont,
+    /// The symbols available in most fonts with good Unicode coverage.
+    Unicode,
+    /// The symbols that only use ASCII characters.
+    Ascii,
+}
+
+impl SymbolSet {
+    /// Parses a symbol set from its name, as used in the configuration file.
+    ///
+    /// # Parameters
+    /// - `name`: the name to be parsed.
+    ///
+    /// # Returns
+    /// The possible symbol set found.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "nerd_font" => Some(Self::NerdFont),
+            "unicode" => Some(Self::Unicode),
+            "ascii" => Some(Self::Ascii),
+            _ => None,
+        }
+    }
+
+    /// Gets the symbols of the set.
+    ///
+    /// # Returns
+    /// The symbols.
+    pub(crate) const fn symbols(&self) -> &'static Symbols {
+        match self {
+            Self::NerdFont => &NERD_FONT_SYMBOLS,
+            Self::Unicode => &UNICODE_SYMBOLS,
+            Self::Ascii => &ASCII_SYMBOLS,
+        }
+    }
+}
+
+/// Represents the default symbols of every section and decorator of the prompt. Symbols that sit
+/// next to other text include their own padding.
+#[derive(Debug)]
+pub(crate) struct Symbols {
+    /// The symbols alternated in the separator at the top of the prompt.
+    pub(crate) top_separator: [&'static str; 2],
+    /// The symbols alternated in the separator that fills the upper line.
+    pub(crate) middle_separator: [&'static str; 2],
+    /// The decorators that open and close the upper line.
+    pub(crate) upper_line_decorators: [&'static str; 2],
+    /// The symbol of the local IP section.
+    pub(crate) local_ip: &'static str,
+    /// The symbols of the disk section, in the order of the `disk::UsageStatus` variants.
+    disk: [&'static str; 3],
+    /// The symbols of the battery section, in the order of the `battery::ChargeStatus` variants,
+    /// each one containing the symbols for when it is not charging and charging.
+    battery: [[&'static str; 2]; 4],
+    /// The symbol of the calendar section.
+    pub(crate) calendar: &'static str,
+    /// The symbols of the clock section, in the order of the `DayFraction` variants.
+    clock: [&'static str; 4],
+    /// The symbol of the command duration section.
+    pub(crate) command_duration: &'static str,
+    /// The symbol of the exit code section.
+    pub(crate) exit_code: &'static str,
+    /// The decorators that open and close the Git section.
+    pub(crate) git_decorators: [&'static str; 2],
+    /// The symbol of the Git section shown before the name of a linked worktree.
+    pub(crate) git_worktree: &'static str,
+    /// The symbol of the Git section shown before the age of the commit checked out.
+    pub(crate) git_age: &'static str,
+    /// The symbols of the Git section shown before the exact tag, the description relative to the
+    /// nearest tag and the hash of the commit checked out when the HEAD is detached.
+    pub(crate) git_detached: [&'static str; 3],
+    /// The symbols of the Git section shown before the number of staged, modified, untracked,
+    /// renamed and conflicted entries, and of dirty submodules.
+    pub(crate) git_changes: [&'static str; 6],
+    /// The symbols of the Git section shown before the number of lines inserted and deleted.
+    pub(crate) git_lines: [&'static str; 2],
+    /// The symbols of the Git section shown before the owner and name of the remote repository hosted
+    /// on GitHub, GitLab, Bitbucket, Codeberg, Gitea or on a self-hosted server.
+    pub(crate) git_forges: [&'static str; 6],
+    /// The symbol of the Git section shown before the branch or bookmark of Mercurial repositories.
+    pub(crate) mercurial: &'static str,
+    /// The symbol of the Git section shown before the bookmarks or change ID of Jujutsu repositories.
+    pub(crate) jujutsu: &'static str,
+    /// The symbol of the Git section shown while its changes are computed in the background.
+    pub(crate) git_pending: &'static str,
+    /// The symbol of the Git section shown when its changes are not computed, as the repository
+    /// configuration skips it.
+    pub(crate) git_unknown: &'static str,
+    /// The symbol of the Git identity section shown before the initials of the author and the domain
+    /// of their email.
+    pub(crate) git_identity: &'static str,
+    /// The symbol of the Git identity section shown when commits are signed.
+    pub(crate) git_signed: &'static str,
+    /// The symbols of the Git section shown before the total of commits ahead of and behind the
+    /// upstream branch.
+    pub(crate) git_ahead_behind: [&'static str; 2],
+    /// The symbol of the Git section shown when the branch has no upstream.
+    pub(crate) git_no_upstream: &'static str,
+    /// The symbol of the Git section shown before the number of stashes.
+    pub(crate) git_stashes: &'static str,
+    /// The symbol of the directory ownership section.
+    pub(crate) directory_ownership: &'static str,
+    /// The symbols of the entry type counts section, in the order of directories, files, sockets,
+    /// fifos, block devices, character devices, symlinks, hidden entries and temporary entries.
+    pub(crate) entry_types: [&'static str; 9],
+    /// The symbol of the jobs section.
+    pub(crate) jobs: &'static str,
+    /// The text shown in place of sections that have not collected their data in time.
+    pub(crate) placeholder: &'static str,
+}
+
+impl Symbols {
+    /// Gets the symbol of the disk section for a usage status.
+    ///
+    /// # Parameters
+    /// - `status`: the status to be considered.
+    ///
+    /// # Returns
+    /// The symbol.
+    pub(crate) const fn disk(&self, status: disk::UsageStatus) -> &'static str {
+        match status {
+            disk::UsageStatus::Low => self.disk[0],
+            disk::UsageStatus::Moderate => self.disk[1],
+            disk::UsageStatus::High => self.disk[2],
+        }
+    }
+
+    /// Gets the symbol of the battery section for a charge status.
+    ///
+    /// # Parameters
+    /// - `status`: the status to be considered.
+    /// - `is_charging`: a boolean that states the battery is charging.
+    ///
+    /// # Returns
+    /// The symbol.
+    pub(crate) const fn battery(
+        &self,
+        status: battery::ChargeStatus,
+        is_charging: bool,
+    ) -> &'static str {
+        let symbols = match status {
+            battery::ChargeStatus::Critical => self.battery[0],
+            battery::ChargeStatus::Low => self.battery[1],
+            battery::ChargeStatus::Moderate => self.battery[2],
+            battery::ChargeStatus::High => self.battery[3],
+        };
+        symbols[is_charging as usize]
+    }
+
+    /// Gets the symbol of the clock section for a fraction of the day.
+    ///
+    /// # Parameters
+    /// - `day_fraction`: the fraction to be considered.
+    ///
+    /// # Returns
+    /// The symbol.
+    pub(crate) const fn clock(&self, day_fraction: DayFraction) -> &'static str {
+        match day_fraction {
+            DayFraction::Dawn => self.clock[0],
+            DayFraction::Morning => self.clock[1],
+            DayFraction::Afternoon => self.clock[2],
+            DayFraction::Night => self.clock[3],
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Provides features to retrieve metadata of repositories from different version control systems.
+
+use std::path::{Path, PathBuf};
+
+use crate::{git, jujutsu, mercurial};
+
+/// The backends of the supported version control systems. Jujutsu comes first, as its repositories
+/// are usually colocated with Git ones, whose metadata would be misleading.
+static BACKENDS: [&dyn Backend; 3] = [&jujutsu::Backend, &git::Backend, &mercurial::Backend];
+
+/// Contains the supported version control systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// Git.
+    Git,
+    /// Jujutsu.
+    Jujutsu,
+    /// Mercurial.
+    Mercurial,
+}
+
+/// Contains the root of a repository found.
+#[derive(Debug, Clone)]
+pub(crate) struct Root {
+    /// The version control system that manages the repository.
+    pub(crate) kind: Kind,
+    /// The full path to the root directory of the repository.
+    pub(crate) path: PathBuf,
+}
+
+/// Contains the metadata every version control system can provide about a repository.
+#[derive(Debug, Clone)]
+pub(crate) struct Summary {
+    /// The version control system that manages the repository.
+    pub(crate) kind: Kind,
+    /// The name of the active branch, bookmark or revision.
+    pub(crate) reference: String,
+    /// The possible ID of the revision checked out, given when the reference is a name.
+    pub(crate) revision: Option<String>,
+    /// Whether the work tree contains uncommited changes. If `None`, it can not be determined.
+    pub(crate) is_dirty: Option<bool>,
+    /// Whether the revision checked out contains unresolved conflicts.
+    pub(crate) is_conflicted: bool,
+}
+
+/// Provides members to retrieve the metadata of repositories of a version control system.
+pub(crate) trait Backend: Sync {
+    /// Gets the version control system the backend refers to.
+    ///
+    /// # Returns
+    /// The version control system.
+    fn kind(&self) -> Kind;
+    /// Finds the root directory of a repository by searching recursively from a directory. It is
+    /// meant to be cheap, as it runs for every backend.
+    ///
+    /// # Parameters
+    /// - `directory`: the directory to start searching from.
+    ///
+    /// # Returns
+    /// The possible full path to the root directory found.
+    fn find_root(&self, directory: &Path) -> Option<PathBuf>;
+    /// Summarizes the metadata of a repository. Git does not provide it, as its richer metadata is
+    /// collected apart, through [`crate::git::Repository`], and shared by the sections that use it.
+    ///
+    /// # Parameters
+    /// - `root`: the full path to the root directory of the repository.
+    ///
+    /// # Returns
+    /// The possible summary, if the repository can be read and the backend provides it.
+    fn summarize(&self, _root: &Path) -> Option<Summary> {
+        None
+    }
+}
+
+/// Gets the backend of a version control system.
+///
+/// # Parameters
+/// - `kind`: the version control system.
+///
+/// # Returns
+/// The backend.
+pub(crate) fn backend(kind: Kind) -> &'static dyn Backend {
+    BACKENDS
+        .into_iter()
+        .find(|backend| backend.kind() == kind)
+        .unwrap_or(BACKENDS[0])
+}
+
+/// Finds the root of the innermost repository of any version control system containing a
+/// directory, so that, e.g., a Mercurial repository inside of a Git repository is preferred. If
+/// repositories share the same root, the one of the first backend is preferred.
+///
+/// # Parameters
+/// - `directory`: the directory to start searching from.
+///
+/// # Returns
+/// The possible root found.
+pub(crate) fn find_root(directory: &Path) -> Option<Root> {
+    BACKENDS
+        .into_iter()
+        .rev()
+        .filter_map(|backend| {
+            backend.find_root(directory).map(|path| Root {
+                kind: backend.kind(),
+                path,
+            })
+        })
+        .max_by_key(|root| root.path.components().count())
+}
+
+/// Finds the root directory of a repository by searching recursively from a directory for the
+/// directory that contains a metadata entry, e.g. `.hg`.
+///
+/// # Parameters
+/// - `directory`: the directory to start searching from.
+/// - `metadata_directory`: the name of the metadata directory.
+///
+/// # Returns
+/// The possible full path to the root directory found.
+pub(crate) fn find_root_containing(directory: &Path, metadata_directory: &str) -> Option<PathBuf> {
+    directory
+        .ancestors()
+        .find(|ancestor| ancestor.join(metadata_directory).is_dir())
+        .map(Path::to_path_buf)
+}